@@ -0,0 +1,258 @@
+//! Simulation of an auditor in a digital identity deployment.
+
+use anyhow::Context;
+use axum::{Json, Router, extract::State, routing::get};
+use didemo_common::{
+    config::{CommonConfiguration, Configuration},
+    credential::{Credential, CredentialType},
+    error::send,
+    linkability::{LinkabilityReport, Presentation},
+    messages::{
+        auditor::{AuditReport, ObservedAttribute},
+        issuer::LedgerEntry,
+        relying_party::AccessDecision,
+    },
+    router::AppError,
+};
+use reqwest::{Client, ClientBuilder};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::collections::HashMap;
+
+/// Configuration for an auditor. Actors are identified by hostnames that the auditor can resolve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditorConfiguration {
+    #[serde(flatten)]
+    pub common: CommonConfiguration,
+
+    /// Persons whose privacy is audited.
+    pub persons: Vec<String>,
+
+    /// Issuers whose ledgers are audited.
+    #[serde(default)]
+    pub issuers: Vec<String>,
+
+    /// Wallets whose credentials are audited.
+    #[serde(default)]
+    pub wallets: Vec<String>,
+
+    /// Relying parties whose access decisions are audited.
+    #[serde(default)]
+    pub relying_parties: Vec<String>,
+
+    /// Presentations made to different relying parties within this many seconds of each other are
+    /// considered linkable by their timing.
+    #[serde(default = "default_timing_window")]
+    pub timing_window: u64,
+}
+
+fn default_timing_window() -> u64 {
+    60
+}
+
+impl Configuration for AuditorConfiguration {
+    fn common_configuration(&self) -> &CommonConfiguration {
+        &self.common
+    }
+}
+
+/// The parts of a person's configuration that the auditor needs.
+#[derive(Debug, Deserialize)]
+struct PersonConfiguration {
+    wallet_hostname: String,
+}
+
+/// Construct an auditor from its configuration. Returns the actor's name and the routes it serves.
+pub fn actor(
+    config: AuditorConfiguration,
+    client_builder: ClientBuilder,
+) -> Result<(String, Router), anyhow::Error> {
+    let http_client = client_builder.build()?;
+
+    let routes = Router::new()
+        .route("/config", get(serve_config))
+        .route("/report", get(serve_report))
+        .route("/linkability", get(serve_linkability))
+        .with_state((config, http_client));
+
+    Ok(("auditor".to_string(), routes))
+}
+
+/// Print the configuration.
+async fn serve_config(
+    State((config, _)): State<(AuditorConfiguration, Client)>,
+) -> Json<AuditorConfiguration> {
+    Json(config)
+}
+
+/// Report what each audited actor observed about each audited person.
+async fn serve_report(
+    State((config, http_client)): State<(AuditorConfiguration, Client)>,
+) -> Result<Json<AuditReport>, AppError> {
+    Ok(Json(audit(&config, &http_client).await?))
+}
+
+/// Report which proofs presented to the audited relying parties they could link, were they to
+/// collude.
+async fn serve_linkability(
+    State((config, http_client)): State<(AuditorConfiguration, Client)>,
+) -> Result<Json<LinkabilityReport>, AppError> {
+    let presentations = presentations(&config, &http_client).await?;
+    Ok(Json(LinkabilityReport::analyze(
+        &presentations,
+        config.timing_window,
+    )))
+}
+
+/// Collect the records kept by each audited actor and work out which attributes of each person
+/// they observed.
+async fn audit(
+    config: &AuditorConfiguration,
+    http_client: &Client,
+) -> Result<AuditReport, anyhow::Error> {
+    let mut report = AuditReport::default();
+
+    // Issuers and wallets don't know persons by their hostnames, only by their wallets.
+    let mut persons_by_wallet = HashMap::new();
+    for person in &config.persons {
+        let person_config: PersonConfiguration = fetch(http_client, person, "config").await?;
+        persons_by_wallet.insert(person_config.wallet_hostname, person.as_str());
+    }
+
+    // Issuers observe every attribute of the credentials they issue.
+    for issuer in &config.issuers {
+        let ledger: Vec<LedgerEntry> = fetch(http_client, issuer, "ledger").await?;
+        for entry in ledger {
+            let Some(person) = persons_by_wallet.get(entry.wallet_hostname.as_str()) else {
+                continue;
+            };
+            let messages = entry
+                .credential_type
+                .bbs_messages(&entry.encoded_credential)?;
+            for attribute in
+                observed_attributes(entry.credential_type, messages.into_iter().enumerate())
+            {
+                report.observe(person, issuer, attribute);
+            }
+        }
+    }
+
+    // Wallets hold every attribute of every credential stored in them.
+    for wallet in &config.wallets {
+        let Some(person) = persons_by_wallet.get(wallet.as_str()) else {
+            continue;
+        };
+        let credentials: Vec<Credential> = fetch(http_client, wallet, "credentials").await?;
+        for credential in credentials {
+            report.observe(
+                person,
+                wallet,
+                issuer_attribute(credential.credential_type, &credential.signature.header),
+            );
+            for attribute in observed_attributes(
+                credential.credential_type,
+                credential.bbs_messages()?.into_iter().enumerate(),
+            ) {
+                report.observe(person, wallet, attribute);
+            }
+        }
+    }
+
+    // Relying parties observe the messages disclosed in proofs, and which issuer signed the
+    // credential each proof was made from.
+    for relying_party in &config.relying_parties {
+        let decisions: Vec<AccessDecision> = fetch(http_client, relying_party, "decisions").await?;
+        for decision in decisions {
+            for outcome in decision.proof_outcomes {
+                let Some(credential_type) = outcome.credential_type else {
+                    continue;
+                };
+                if let Some(header) = &outcome.header {
+                    report.observe(
+                        &decision.person_hostname,
+                        relying_party,
+                        issuer_attribute(credential_type, header),
+                    );
+                }
+                for attribute in observed_attributes(credential_type, outcome.disclosed_messages) {
+                    report.observe(&decision.person_hostname, relying_party, attribute);
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Collect the proofs that were presented to the audited relying parties and verified.
+async fn presentations(
+    config: &AuditorConfiguration,
+    http_client: &Client,
+) -> Result<Vec<Presentation>, anyhow::Error> {
+    let mut presentations = Vec::new();
+    for relying_party in &config.relying_parties {
+        let decisions: Vec<AccessDecision> = fetch(http_client, relying_party, "decisions").await?;
+        for decision in decisions {
+            for outcome in decision.proof_outcomes {
+                let (Some(credential_type), Some(header), Some(proof), None) = (
+                    outcome.credential_type,
+                    outcome.header,
+                    outcome.proof,
+                    outcome.failure,
+                ) else {
+                    continue;
+                };
+                presentations.push(Presentation {
+                    person: decision.person_hostname.clone(),
+                    relying_party: relying_party.clone(),
+                    proof_type: outcome.proof_type,
+                    credential_type,
+                    header,
+                    proof,
+                    disclosed_messages: outcome.disclosed_messages,
+                    presented_at: decision.decided_at,
+                });
+            }
+        }
+    }
+
+    Ok(presentations)
+}
+
+/// Fetch a JSON object from some path on a host.
+async fn fetch<T: DeserializeOwned>(
+    http_client: &Client,
+    host: &str,
+    path: &str,
+) -> Result<T, anyhow::Error> {
+    send(host, http_client.get(format!("http://{host}/{path}")))
+        .await?
+        .json()
+        .await
+        .context(format!("failed to deserialize {path} from {host}"))
+}
+
+/// Decode BBS messages, each tupled with its index, into the attributes they encode.
+fn observed_attributes(
+    credential_type: CredentialType,
+    messages: impl IntoIterator<Item = (usize, Vec<u8>)>,
+) -> impl Iterator<Item = ObservedAttribute> {
+    messages
+        .into_iter()
+        .map(move |(index, message)| ObservedAttribute {
+            credential_type,
+            name: credential_type.attribute_name(index),
+            value: credential_type
+                .attributes()
+                .get(index)
+                .and_then(|(_, encoding)| encoding.decode(&message)),
+        })
+}
+
+/// The issuer of a credential, named by the header of its signature.
+fn issuer_attribute(credential_type: CredentialType, header: &[u8]) -> ObservedAttribute {
+    ObservedAttribute {
+        credential_type,
+        name: "issuer".to_string(),
+        value: String::from_utf8(header.to_vec()).ok(),
+    }
+}
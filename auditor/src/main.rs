@@ -0,0 +1,7 @@
+use didemo_auditor::actor;
+use didemo_common::router::actor_main;
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    actor_main(actor).await
+}
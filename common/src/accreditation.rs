@@ -0,0 +1,134 @@
+//! Accreditation of issuers by jurisdictions.
+//!
+//! Issuers declare which types of credentials they issue, but nothing stops a library from
+//! declaring that it issues driver's licenses. Jurisdictions accredit issuers to issue specific
+//! types of credentials and publish a signed list of their accreditations. Wallets and verifiers
+//! that trust a jurisdiction only accept credentials from issuers it accredited for the credential's
+//! type.
+
+use crate::{
+    bbs::{BbsKeypair, BbsPublicKey},
+    credential::CredentialType,
+};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// An issuer's accreditation to issue some types of credentials.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Accreditation {
+    /// The issuer's name, as it appears in the header of its BBS signatures.
+    pub issuer: String,
+
+    /// Types of credentials the issuer is accredited to issue.
+    pub credential_types: Vec<CredentialType>,
+}
+
+/// Whether any of the accreditations accredits the issuer to issue the type of credential.
+pub fn accredits(
+    accreditations: &[Accreditation],
+    issuer: &str,
+    credential_type: CredentialType,
+) -> bool {
+    accreditations.iter().any(|accreditation| {
+        accreditation.issuer == issuer && accreditation.credential_types.contains(&credential_type)
+    })
+}
+
+/// Sign accreditations so wallets and verifiers can check they were published by the jurisdiction.
+pub fn sign(
+    jurisdiction: &str,
+    accreditations: &[Accreditation],
+    keypair: &BbsKeypair,
+) -> Result<SignedAccreditations, anyhow::Error> {
+    Ok(SignedAccreditations {
+        jurisdiction: jurisdiction.to_string(),
+        signature: keypair.sign(
+            jurisdiction.as_bytes().to_vec(),
+            Vec::from([encode(accreditations)?]),
+        )?,
+        accreditations: accreditations.to_vec(),
+    })
+}
+
+/// Accreditations signed by the jurisdiction that published them.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignedAccreditations {
+    /// The jurisdiction's name, as it appears in the header of its BBS signatures.
+    pub jurisdiction: String,
+
+    /// The accreditations.
+    pub accreditations: Vec<Accreditation>,
+
+    /// BBS signature over the accreditations, with the jurisdiction's name as the header.
+    pub signature: Vec<u8>,
+}
+
+impl SignedAccreditations {
+    /// Verify the signature over the accreditations using the jurisdiction's public key and return
+    /// them.
+    pub fn verify(&self, public_key: &BbsPublicKey) -> Result<&[Accreditation], anyhow::Error> {
+        public_key.verify(
+            self.jurisdiction.as_bytes().to_vec(),
+            Vec::from([encode(&self.accreditations)?]),
+            self.signature.clone(),
+        )?;
+        Ok(&self.accreditations)
+    }
+}
+
+/// Encode accreditations into the BBS message that is signed over them.
+fn encode(accreditations: &[Accreditation]) -> Result<Vec<u8>, anyhow::Error> {
+    serde_json::to_vec(accreditations).context("failed to encode accreditations")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Accreditation, accredits, sign};
+    use crate::{bbs::BbsKeypair, credential::CredentialType};
+
+    #[test]
+    fn signed_accreditations() {
+        let keypair = BbsKeypair::generate().unwrap();
+        let accreditations = [
+            Accreditation {
+                issuer: "issuer/dmv-1".to_string(),
+                credential_types: Vec::from([CredentialType::DriversLicense]),
+            },
+            Accreditation {
+                issuer: "issuer/library-1".to_string(),
+                credential_types: Vec::from([CredentialType::LibraryCard]),
+            },
+        ];
+
+        let signed = sign("jurisdiction/test", &accreditations, &keypair).unwrap();
+        let verified = signed.verify(&keypair.public_key()).unwrap();
+        assert_eq!(verified, accreditations);
+        assert!(accredits(
+            verified,
+            "issuer/dmv-1",
+            CredentialType::DriversLicense
+        ));
+        assert!(!accredits(
+            verified,
+            "issuer/library-1",
+            CredentialType::DriversLicense
+        ));
+        assert!(!accredits(
+            verified,
+            "issuer/dmv-2",
+            CredentialType::DriversLicense
+        ));
+
+        // An issuer can't accredit itself by tampering with the accreditations.
+        let mut tampered = signed.clone();
+        tampered.accreditations[1]
+            .credential_types
+            .push(CredentialType::DriversLicense);
+        tampered.verify(&keypair.public_key()).unwrap_err();
+
+        // Nor do accreditations verify with another jurisdiction's key.
+        signed
+            .verify(&BbsKeypair::generate().unwrap().public_key())
+            .unwrap_err();
+    }
+}
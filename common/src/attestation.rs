@@ -0,0 +1,71 @@
+//! Attestations by wallet makers that they made a wallet.
+//!
+//! Issuers may only be willing to issue credentials into wallets made by vendors they trust to
+//! protect them. A wallet maker attests that it made a wallet by signing the hostname at which the
+//! wallet is reached, and the wallet presents that attestation to issuers.
+
+use crate::bbs::{BbsKeypair, BbsPublicKey};
+use serde::{Deserialize, Serialize};
+
+/// A wallet maker's attestation that it made the wallet reached at some hostname.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WalletAttestation {
+    /// The vendor that made the wallet, as it appears in the header of its BBS signatures.
+    pub vendor: String,
+
+    /// The hostname at which the wallet is reached.
+    pub wallet_hostname: String,
+
+    /// BBS signature over the wallet hostname, with the vendor as the header.
+    pub signature: Vec<u8>,
+}
+
+impl WalletAttestation {
+    /// Attest that the vendor made the wallet reached at the hostname.
+    pub fn sign(
+        vendor: &str,
+        wallet_hostname: &str,
+        keypair: &BbsKeypair,
+    ) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            vendor: vendor.to_string(),
+            wallet_hostname: wallet_hostname.to_string(),
+            signature: keypair.sign(
+                vendor.as_bytes().to_vec(),
+                Vec::from([wallet_hostname.as_bytes().to_vec()]),
+            )?,
+        })
+    }
+
+    /// Verify the signature over the attestation using the vendor's public key.
+    pub fn verify(&self, public_key: &BbsPublicKey) -> Result<(), anyhow::Error> {
+        public_key.verify(
+            self.vendor.as_bytes().to_vec(),
+            Vec::from([self.wallet_hostname.as_bytes().to_vec()]),
+            self.signature.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WalletAttestation;
+    use crate::bbs::BbsKeypair;
+
+    #[test]
+    fn wallet_attestation() {
+        let keypair = BbsKeypair::generate().unwrap();
+        let attestation = WalletAttestation::sign("vendor", "wallet", &keypair).unwrap();
+        attestation.verify(&keypair.public_key()).unwrap();
+
+        // An attestation for one wallet can't be presented by another.
+        let mut tampered = attestation.clone();
+        tampered.wallet_hostname = "other-wallet".to_string();
+        tampered.verify(&keypair.public_key()).unwrap_err();
+
+        // Nor can another vendor's key verify it.
+        attestation
+            .verify(&BbsKeypair::generate().unwrap().public_key())
+            .unwrap_err();
+    }
+}
@@ -1,20 +1,39 @@
 //! Utilities for working with BBS signatures and pairing_crypto.
 
+use crate::error::Error;
 use anyhow::{Context, anyhow};
-use pairing_crypto::bbs::{
-    BbsProofGenRevealMessageRequest, BbsProofVerifyRequest, BbsSignRequest, BbsVerifyRequest,
-    ciphersuites::{
-        bls12_381::{BBS_BLS12381G1_SIGNATURE_LENGTH, KeyPair},
-        bls12_381_g1_sha_256::{proof_gen, proof_verify, sign, verify},
+use pairing_crypto::{
+    bbs::{
+        BbsSignRequest, BbsVerifyRequest,
+        ciphersuites::{
+            bls12_381::{
+                BBS_BLS12381G1_PUBLIC_KEY_LENGTH, BBS_BLS12381G1_SIGNATURE_LENGTH, KeyPair,
+                PublicKey, SecretKey,
+            },
+            bls12_381_g1_sha_256::{sign, verify},
+        },
     },
+    bbs_bound::{
+        BbsBoundProofGenRequest, BbsBoundProofGenRevealMessageRequest, BbsBoundProofVerifyRequest,
+        BbsBoundSignRequest, BbsBoundVerifyRequest, BlsKeyPopGenRequest, BlsKeyPopVerifyRequest,
+        ciphersuites::bls12_381_bbs_g1_bls_sig_g2_sha_256 as bound,
+    },
+    bls::ciphersuites::bls12_381::{
+        BLS_SIG_BLS12381G2_PUBLIC_KEY_LENGTH, BLS_SIG_BLS12381G2_SECRET_KEY_LENGTH,
+        BLS_SIG_BLS12381G2_SIGNATURE_LENGTH, KeyPair as BlsKeyPair, PublicKey as BlsPublicKey,
+        SecretKey as BlsSecretKey,
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
 };
 
 /// A BBS keypair used for signing credentials.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BbsKeypair {
-    /// Name of the actor that holds the private portion of this keypair. Used to derive keys.
-    actor_name: String,
-
     /// The keypair.
     keypair: KeyPair,
 }
@@ -24,10 +43,10 @@ impl BbsKeypair {
     ///
     /// # Discussion
     ///
-    /// Deterministic keys is a cheat since we don't yet have a public key distribution mechanism.
+    /// Anyone who knows the actor name can derive the private key, so this is only suitable for
+    /// tests. Issuers should use [`BbsKeypair::generate`].
     pub fn new(actor_name: &str) -> Result<Self, anyhow::Error> {
         Ok(Self {
-            actor_name: actor_name.to_string(),
             // Using this fixed seed is not secure but this is harmless in the simulation setup.
             keypair: KeyPair::new(
                 b"00000000000000000000000000000000",
@@ -37,6 +56,64 @@ impl BbsKeypair {
         })
     }
 
+    /// Generate a keypair from random key material.
+    pub fn generate() -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            keypair: KeyPair::new(&rand::random::<[u8; 32]>(), b"didemo")
+                .ok_or(anyhow!("failed to generate BBS key"))?,
+        })
+    }
+
+    /// Read a keypair from a file written by [`BbsKeypair::write_to_file`].
+    pub fn read_from_file(path: &Path) -> Result<Self, anyhow::Error> {
+        let secret_key = SecretKey::from_vec(&read_hex_file(path)?)
+            .map_err(|error| anyhow!("invalid BBS secret key in {}: {error:?}", path.display()))?;
+        Ok(Self {
+            keypair: KeyPair {
+                public_key: PublicKey::from(&secret_key),
+                secret_key,
+            },
+        })
+    }
+
+    /// Write the private portion of this keypair to a file, as hex, readable only by its owner. The
+    /// public portion is derived from it when the file is read. Use [`BbsPublicKey::write_to_file`]
+    /// to write a file that can be distributed.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), anyhow::Error> {
+        write_hex_file(path, &self.keypair.secret_key.to_bytes(), 0o600)
+    }
+
+    /// Read a keypair from the key file, as written by [`BbsKeypair::write_to_key_files`]. If the
+    /// file doesn't exist, generate a keypair and write it there.
+    pub fn read_or_generate(key_file: &Path) -> Result<Self, anyhow::Error> {
+        if key_file.exists() {
+            return Self::read_from_file(key_file);
+        }
+
+        let keypair = Self::generate()?;
+        keypair.write_to_key_files(key_file)?;
+        tracing::info!(key_file = %key_file.display(), "generated signing key");
+
+        Ok(keypair)
+    }
+
+    /// Write this keypair to the key file with [`BbsKeypair::write_to_file`], and its public portion
+    /// alongside it with [`BbsPublicKey::write_to_file`], in a file with `.pub` appended to the
+    /// name.
+    pub fn write_to_key_files(&self, key_file: &Path) -> Result<(), anyhow::Error> {
+        let mut public_key_file = key_file.to_path_buf().into_os_string();
+        public_key_file.push(".pub");
+
+        self.write_to_file(key_file)?;
+        self.public_key()
+            .write_to_file(&PathBuf::from(public_key_file))
+    }
+
+    /// The public portion of this keypair, which can be distributed to wallets and verifiers.
+    pub fn public_key(&self) -> BbsPublicKey {
+        BbsPublicKey(self.keypair.public_key.to_octets().to_vec())
+    }
+
     /// Sign a message with a header with this key.
     // TODO: take header and messages as references to slices.
     pub fn sign(&self, header: Vec<u8>, messages: Vec<Vec<u8>>) -> Result<Vec<u8>, anyhow::Error> {
@@ -50,6 +127,54 @@ impl BbsKeypair {
         .context("failed to sign messages")
     }
 
+    /// Sign messages with a header with this key, binding the signature to a holder's key. Only the
+    /// holder of its secret key can verify the signature or prove anything from it, and the secret
+    /// key is never revealed to the signer. Check that the holder possesses it with
+    /// [`HolderPublicKey::verify_possession`] first.
+    pub fn sign_bound(
+        &self,
+        header: Vec<u8>,
+        messages: Vec<Vec<u8>>,
+        holder_public_key: &HolderPublicKey,
+    ) -> Result<Vec<u8>, anyhow::Error> {
+        bound::sign(&BbsBoundSignRequest {
+            secret_key: &self.keypair.secret_key.to_bytes(),
+            public_key: &self.keypair.public_key.to_octets(),
+            bls_public_key: &holder_public_key.to_array()?,
+            header: Some(header),
+            messages: Some(&messages),
+        })
+        .map(|s| s.to_vec())
+        .context("failed to sign messages bound to holder key")
+    }
+}
+
+/// The public portion of a BBS keypair, used to verify signatures and to generate and verify
+/// proofs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BbsPublicKey(Vec<u8>);
+
+impl BbsPublicKey {
+    /// Read a public key from a file written by [`BbsPublicKey::write_to_file`].
+    pub fn read_from_file(path: &Path) -> Result<Self, anyhow::Error> {
+        let public_key = Self(read_hex_file(path)?);
+        PublicKey::from_vec(&public_key.0)
+            .map_err(|error| anyhow!("invalid BBS public key in {}: {error:?}", path.display()))?;
+        Ok(public_key)
+    }
+
+    /// Write this public key to a file, as hex.
+    pub fn write_to_file(&self, path: &Path) -> Result<(), anyhow::Error> {
+        write_hex_file(path, &self.0, 0o644)
+    }
+
+    /// A short identifier for this key. It is derived from the key, so a key read from a file keeps
+    /// its identifier. The trailing bytes of a compressed point are uniformly distributed, so they
+    /// serve as well as a hash of the key.
+    pub fn key_id(&self) -> String {
+        hex::encode(&self.0[self.0.len().saturating_sub(8)..])
+    }
+
     /// Verify a signature over a message and header using this key.
     // TODO: take arguments as slices.
     pub fn verify(
@@ -58,68 +183,250 @@ impl BbsKeypair {
         messages: Vec<Vec<u8>>,
         signature: Vec<u8>,
     ) -> Result<(), anyhow::Error> {
-        if verify(&BbsVerifyRequest {
-            public_key: &self.keypair.public_key.to_octets(),
+        match verify(&BbsVerifyRequest {
+            public_key: &self.to_array()?,
             header: Some(header),
             messages: Some(&messages),
             signature: &signature_to_array(signature)?,
-        })
-        .context("failed to verify BBS signature")?
-        {
-            Ok(())
-        } else {
-            Err(anyhow!("BBS signature invalid"))
+        }) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::InvalidSignature {
+                message: "BBS signature invalid".to_string(),
+            }
+            .into()),
+            Err(error) => Err(Error::InvalidSignature {
+                message: format!("failed to verify BBS signature: {error:?}"),
+            }
+            .into()),
+        }
+    }
+
+    /// Verify a signature over a message and header using this key, bound to the holder's key by
+    /// [`BbsKeypair::sign_bound`].
+    pub fn verify_bound(
+        &self,
+        header: Vec<u8>,
+        messages: Vec<Vec<u8>>,
+        signature: Vec<u8>,
+        holder_keypair: &HolderKeypair,
+    ) -> Result<(), anyhow::Error> {
+        match bound::verify(&BbsBoundVerifyRequest {
+            public_key: &self.to_array()?,
+            bls_secret_key: &holder_keypair.keypair.secret_key.to_bytes(),
+            header: Some(header),
+            messages: Some(&messages),
+            signature: &signature_to_array(signature)?,
+        }) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::InvalidSignature {
+                message: "BBS signature invalid or bound to another holder".to_string(),
+            }
+            .into()),
+            Err(error) => Err(Error::InvalidSignature {
+                message: format!("failed to verify BBS signature: {error:?}"),
+            }
+            .into()),
         }
     }
 
-    /// Prove one or more messages from a signature. Messages are tuples; the boolean indicates
-    /// whether the message should be revealed with the proof.
+    /// Prove one or more messages from a signature bound to the holder's key. Messages are tuples;
+    /// the boolean indicates whether the message should be revealed with the proof. The proof is
+    /// bound to the presentation header, which the verifier must supply to verify the proof. It
+    /// shows that the prover holds the holder's secret key, without revealing it.
     pub fn prove(
         &self,
         header: Vec<u8>,
+        presentation_header: Vec<u8>,
         messages: Vec<(bool, Vec<u8>)>,
         signature: Vec<u8>,
+        holder_keypair: &HolderKeypair,
     ) -> Result<Vec<u8>, anyhow::Error> {
         let proof_gen_reveals: Vec<_> = messages
             .into_iter()
-            .map(|(reveal, value)| BbsProofGenRevealMessageRequest { reveal, value })
+            .map(|(reveal, value)| BbsBoundProofGenRevealMessageRequest { reveal, value })
             .collect();
-        proof_gen(&pairing_crypto::bbs::BbsProofGenRequest {
-            public_key: &self.keypair.public_key.to_octets(),
+        bound::proof_gen(&BbsBoundProofGenRequest {
+            public_key: &self.to_array()?,
+            bls_secret_key: &holder_keypair.keypair.secret_key.to_bytes(),
             header: Some(header),
             messages: Some(&proof_gen_reveals),
             signature: &signature_to_array(signature)?,
-            // TODO: Most definitely want to bind the proof to something like say an authentication
-            // session or at least a particular verifier.
-            presentation_header: None,
+            presentation_header: Some(presentation_header),
             // why on earth is this an optional boolean? What does None mean that false wouldn't?!
             verify_signature: Some(false),
         })
         .context("failed to BBS prove messages")
     }
 
-    /// Verify one or more messages against a signature
+    /// Verify one or more messages against a signature bound to some holder's key, checking that
+    /// the proof is bound to the presentation header. The verifier learns nothing about the
+    /// holder's key.
     pub fn verify_proof(
         &self,
         header: Vec<u8>,
+        presentation_header: Vec<u8>,
         disclosed_messages: Vec<(usize, Vec<u8>)>,
         proof: Vec<u8>,
     ) -> Result<(), anyhow::Error> {
-        if proof_verify(&BbsProofVerifyRequest {
-            public_key: &self.keypair.public_key.to_octets(),
+        match bound::proof_verify(&BbsBoundProofVerifyRequest {
+            public_key: &self.to_array()?,
             header: Some(header),
-            // TODO: presentation header binding to context
-            presentation_header: None,
+            presentation_header: Some(presentation_header),
             proof: &proof,
             messages: Some(&disclosed_messages),
+        }) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::InvalidSignature {
+                message: "BBS proof invalid".to_string(),
+            }
+            .into()),
+            Err(error) => Err(Error::InvalidSignature {
+                message: format!("failed to verify BBS proof: {error:?}"),
+            }
+            .into()),
+        }
+    }
+
+    fn to_array(&self) -> Result<[u8; BBS_BLS12381G1_PUBLIC_KEY_LENGTH], anyhow::Error> {
+        self.0
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("failed to convert public key to array"))
+    }
+}
+
+/// A holder's BLS keypair, to which issuers bind the credentials they issue with
+/// [`BbsKeypair::sign_bound`]. The issuer only sees the public key, which commits to the secret key,
+/// and proofs made from the credential require the secret key without revealing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HolderKeypair {
+    /// The keypair.
+    keypair: BlsKeyPair,
+}
+
+impl HolderKeypair {
+    /// Generate a keypair from random key material.
+    pub fn generate() -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            keypair: BlsKeyPair::new(&rand::random::<[u8; 32]>(), b"didemo-holder")
+                .ok_or(anyhow!("failed to generate holder key"))?,
+        })
+    }
+
+    /// Reconstruct a keypair from its secret key, as returned by [`HolderKeypair::secret_key`].
+    pub fn from_secret_key(secret_key: &[u8]) -> Result<Self, anyhow::Error> {
+        let secret_key: [u8; BLS_SIG_BLS12381G2_SECRET_KEY_LENGTH] = secret_key
+            .try_into()
+            .map_err(|_| anyhow!("failed to convert holder secret key to array"))?;
+        let secret_key = BlsSecretKey::from_bytes(&secret_key)
+            .map_err(|error| anyhow!("invalid holder secret key: {error:?}"))?;
+        Ok(Self {
+            keypair: BlsKeyPair {
+                public_key: BlsPublicKey::from(&secret_key),
+                secret_key,
+            },
+        })
+    }
+
+    /// The secret key, which must never leave the holder.
+    pub fn secret_key(&self) -> Vec<u8> {
+        self.keypair.secret_key.to_bytes().to_vec()
+    }
+
+    /// The public key, which the holder hands to issuers.
+    pub fn public_key(&self) -> HolderPublicKey {
+        HolderPublicKey(self.keypair.public_key.to_octets().to_vec())
+    }
+
+    /// Prove possession of the secret key to the audience, e.g. the issuer about to bind a
+    /// credential to the public key.
+    pub fn prove_possession(&self, audience: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+        bound::bls_key_pop(&BlsKeyPopGenRequest {
+            bls_secret_key: &self.keypair.secret_key.to_bytes(),
+            aud: audience,
+            dst: None,
+            extra_info: None,
         })
-        .context("failed to verify BBS proofs")?
-        {
-            Ok(())
-        } else {
-            Err(anyhow!("BBS proof invalid"))
+        .map(|pop| pop.to_vec())
+        .context("failed to prove possession of holder key")
+    }
+}
+
+/// The public portion of a [`HolderKeypair`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HolderPublicKey(Vec<u8>);
+
+impl HolderPublicKey {
+    /// Verify that the holder possesses the secret key, as proven to the audience by
+    /// [`HolderKeypair::prove_possession`].
+    pub fn verify_possession(
+        &self,
+        audience: &[u8],
+        proof_of_possession: Vec<u8>,
+    ) -> Result<(), anyhow::Error> {
+        let proof_of_possession: [u8; BLS_SIG_BLS12381G2_SIGNATURE_LENGTH] = proof_of_possession
+            .try_into()
+            .map_err(|_| anyhow!("failed to convert proof of possession to array"))?;
+        match bound::bls_key_pop_verify(&BlsKeyPopVerifyRequest {
+            bls_key_pop: &proof_of_possession,
+            bls_public_key: &self.to_array()?,
+            aud: audience,
+            dst: None,
+            extra_info: None,
+        }) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::InvalidSignature {
+                message: "proof of possession of holder key invalid".to_string(),
+            }
+            .into()),
+            Err(error) => Err(Error::InvalidSignature {
+                message: format!("failed to verify proof of possession of holder key: {error:?}"),
+            }
+            .into()),
         }
     }
+
+    fn to_array(&self) -> Result<[u8; BLS_SIG_BLS12381G2_PUBLIC_KEY_LENGTH], anyhow::Error> {
+        self.0
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow!("failed to convert holder public key to array"))
+    }
+}
+
+/// Read a file holding hex, ignoring surrounding whitespace.
+fn read_hex_file(path: &Path) -> Result<Vec<u8>, anyhow::Error> {
+    let contents =
+        fs::read_to_string(path).context(format!("failed to read {}", path.display()))?;
+    hex::decode(contents.trim()).context(format!("failed to decode hex in {}", path.display()))
+}
+
+/// Write bytes to a file as hex, creating it with the permissions in `mode` on Unix. The file is
+/// replaced atomically, so a crash never leaves a partially written key behind.
+fn write_hex_file(path: &Path, bytes: &[u8], mode: u32) -> Result<(), anyhow::Error> {
+    let mut temp_path = path.to_path_buf().into_os_string();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, mode);
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    let mut temp_file = options
+        .open(&temp_path)
+        .context(format!("failed to create {}", temp_path.display()))?;
+    writeln!(temp_file, "{}", hex::encode(bytes))
+        .and_then(|()| temp_file.sync_all())
+        .context(format!("failed to write {}", temp_path.display()))?;
+
+    fs::rename(&temp_path, path).context(format!(
+        "failed to rename {} to {}",
+        temp_path.display(),
+        path.display()
+    ))
 }
 
 fn signature_to_array(
@@ -132,7 +439,37 @@ fn signature_to_array(
 
 #[cfg(test)]
 mod tests {
-    use super::BbsKeypair;
+    use super::{BbsKeypair, BbsPublicKey, HolderKeypair};
+    use std::{env, fs, process};
+
+    fn messages() -> Vec<Vec<u8>> {
+        Vec::from([b"message-0".to_vec(), b"message-1".to_vec()])
+    }
+
+    fn prove_second_message(
+        keypair: &BbsKeypair,
+        holder_keypair: &HolderKeypair,
+        presentation_header: &[u8],
+    ) -> Vec<u8> {
+        let signature = keypair
+            .sign_bound(b"header".to_vec(), messages(), &holder_keypair.public_key())
+            .unwrap();
+
+        keypair
+            .public_key()
+            .prove(
+                b"header".to_vec(),
+                presentation_header.to_vec(),
+                messages()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, message)| (index == 1, message))
+                    .collect(),
+                signature,
+                holder_keypair,
+            )
+            .unwrap()
+    }
 
     #[test]
     fn keygen_deterministic() {
@@ -149,4 +486,171 @@ mod tests {
 
         assert_ne!(keypair, other_keypair);
     }
+
+    #[test]
+    fn keygen_random() {
+        let keypair = BbsKeypair::generate().unwrap();
+        let other_keypair = BbsKeypair::generate().unwrap();
+
+        assert_ne!(keypair, other_keypair);
+        assert_ne!(keypair.public_key(), other_keypair.public_key());
+    }
+
+    #[test]
+    fn key_files() {
+        let dir = env::temp_dir().join(format!("didemo-bbs-key-files-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let keypair = BbsKeypair::generate().unwrap();
+
+        keypair.write_to_file(&dir.join("key")).unwrap();
+        keypair
+            .public_key()
+            .write_to_file(&dir.join("key.pub"))
+            .unwrap();
+
+        assert_eq!(
+            BbsKeypair::read_from_file(&dir.join("key")).unwrap(),
+            keypair
+        );
+        assert_eq!(
+            BbsPublicKey::read_from_file(&dir.join("key.pub")).unwrap(),
+            keypair.public_key()
+        );
+        // The public key file holds no secrets, and isn't a keypair.
+        BbsKeypair::read_from_file(&dir.join("key.pub")).unwrap_err();
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let keypair = BbsKeypair::generate().unwrap();
+        let signature = keypair.sign(b"header".to_vec(), messages()).unwrap();
+
+        keypair
+            .public_key()
+            .verify(b"header".to_vec(), messages(), signature.clone())
+            .unwrap();
+
+        // Another key can't verify the signature.
+        BbsKeypair::generate()
+            .unwrap()
+            .public_key()
+            .verify(b"header".to_vec(), messages(), signature)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn holder_binding() {
+        let keypair = BbsKeypair::generate().unwrap();
+        let holder_keypair = HolderKeypair::generate().unwrap();
+        let holder_public_key = holder_keypair.public_key();
+
+        // The issuer checks the holder possesses the key, as proven to it and nobody else.
+        let proof_of_possession = holder_keypair.prove_possession(b"issuer").unwrap();
+        holder_public_key
+            .verify_possession(b"issuer", proof_of_possession.clone())
+            .unwrap();
+        holder_public_key
+            .verify_possession(b"other-issuer", proof_of_possession)
+            .unwrap_err();
+
+        let signature = keypair
+            .sign_bound(b"header".to_vec(), messages(), &holder_public_key)
+            .unwrap();
+        keypair
+            .public_key()
+            .verify_bound(
+                b"header".to_vec(),
+                messages(),
+                signature.clone(),
+                &holder_keypair,
+            )
+            .unwrap();
+
+        // The signature only verifies with the holder's key, which survives a round trip through
+        // storage.
+        let stored_holder_keypair =
+            HolderKeypair::from_secret_key(&holder_keypair.secret_key()).unwrap();
+        assert_eq!(stored_holder_keypair, holder_keypair);
+        keypair
+            .public_key()
+            .verify_bound(
+                b"header".to_vec(),
+                messages(),
+                signature,
+                &HolderKeypair::generate().unwrap(),
+            )
+            .unwrap_err();
+    }
+
+    #[test]
+    fn prove_and_verify() {
+        let keypair = BbsKeypair::new("test-1").unwrap();
+        let holder_keypair = HolderKeypair::generate().unwrap();
+        let proof = prove_second_message(&keypair, &holder_keypair, b"presentation-header");
+
+        keypair
+            .public_key()
+            .verify_proof(
+                b"header".to_vec(),
+                b"presentation-header".to_vec(),
+                Vec::from([(1, b"message-1".to_vec())]),
+                proof,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn proof_requires_holder_key() {
+        let keypair = BbsKeypair::new("test-1").unwrap();
+        let holder_keypair = HolderKeypair::generate().unwrap();
+        let proof = prove_second_message(&keypair, &holder_keypair, b"presentation-header");
+
+        // Someone with the signature but another key can't make a proof that verifies.
+        let signature = keypair
+            .sign_bound(b"header".to_vec(), messages(), &holder_keypair.public_key())
+            .unwrap();
+        let forged_proof = keypair.public_key().prove(
+            b"header".to_vec(),
+            b"presentation-header".to_vec(),
+            Vec::from([
+                (false, b"message-0".to_vec()),
+                (true, b"message-1".to_vec()),
+            ]),
+            signature,
+            &HolderKeypair::generate().unwrap(),
+        );
+        let verify = |proof| {
+            keypair.public_key().verify_proof(
+                b"header".to_vec(),
+                b"presentation-header".to_vec(),
+                Vec::from([(1, b"message-1".to_vec())]),
+                proof,
+            )
+        };
+
+        verify(proof).unwrap();
+        // Either no proof can be made, or the proof doesn't verify.
+        if let Ok(forged_proof) = forged_proof {
+            verify(forged_proof).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn proof_bound_to_presentation_header() {
+        let keypair = BbsKeypair::new("test-1").unwrap();
+        let holder_keypair = HolderKeypair::generate().unwrap();
+        let proof = prove_second_message(&keypair, &holder_keypair, b"presentation-header");
+
+        keypair
+            .public_key()
+            .verify_proof(
+                b"header".to_vec(),
+                b"other-presentation-header".to_vec(),
+                Vec::from([(1, b"message-1".to_vec())]),
+                proof,
+            )
+            .unwrap_err();
+    }
 }
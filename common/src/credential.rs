@@ -1,5 +1,6 @@
 //! Common definitions for representing and working with credentials.
 
+use anyhow::{Context, anyhow};
 use serde::{Deserialize, Serialize};
 
 /// Possible types of credentials.
@@ -9,6 +10,187 @@ pub enum CredentialType {
     DriversLicense,
 }
 
+impl CredentialType {
+    /// Index of the BBS message holding the credential's type, as [`Self::name`] writes it. It is
+    /// the same for every type of credential, so that a verifier can tell from the messages
+    /// disclosed in a proof what type of credential it was made from.
+    pub const MESSAGE_INDEX: usize = 0;
+
+    /// Attributes that every type of credential has and that verifiers check on their own terms,
+    /// rather than selecting them through a proof type.
+    pub const RESERVED_ATTRIBUTES: [&'static str; 4] = [
+        "credential_type",
+        "status_list_index",
+        "valid_from",
+        "valid_until",
+    ];
+
+    /// The name of this type of credential, as signed into credentials of this type.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::LibraryCard => "LibraryCard",
+            Self::DriversLicense => "DriversLicense",
+        }
+    }
+
+    /// The type of credential that a proof disclosing the messages was made from. Fails if the
+    /// proof doesn't disclose the credential's type.
+    pub fn from_disclosed_messages(
+        disclosed_messages: &[(usize, Vec<u8>)],
+    ) -> Result<Self, anyhow::Error> {
+        let (_, message) = disclosed_messages
+            .iter()
+            .find(|(index, _)| *index == Self::MESSAGE_INDEX)
+            .ok_or_else(|| anyhow!("proof should disclose credential_type"))?;
+        [Self::LibraryCard, Self::DriversLicense]
+            .into_iter()
+            .find(|credential_type| credential_type.name().as_bytes() == message)
+            .ok_or_else(|| {
+                anyhow!(
+                    "unknown credential type {}",
+                    String::from_utf8_lossy(message)
+                )
+            })
+    }
+
+    /// Index of the BBS message holding the credential's index in its issuer's status list.
+    pub fn status_message_index(&self) -> usize {
+        self.reserved_attribute_index("status_list_index")
+    }
+
+    /// Index of the BBS message holding the time at which the credential becomes valid.
+    pub fn valid_from_message_index(&self) -> usize {
+        self.reserved_attribute_index("valid_from")
+    }
+
+    /// Index of the BBS message holding the time at which the credential expires.
+    pub fn valid_until_message_index(&self) -> usize {
+        self.reserved_attribute_index("valid_until")
+    }
+
+    /// Index of one of the [`Self::RESERVED_ATTRIBUTES`], which every type of credential has.
+    fn reserved_attribute_index(&self, name: &str) -> usize {
+        self.attribute_index(name)
+            .unwrap_or_else(|| panic!("{self:?} should have attribute {name}"))
+    }
+
+    /// Names and encodings of the attributes of this type of credential, in the order of their BBS
+    /// messages.
+    pub fn attributes(&self) -> &'static [(&'static str, AttributeEncoding)] {
+        use AttributeEncoding::*;
+        match self {
+            Self::LibraryCard => &[
+                ("credential_type", String),
+                ("library_name", String),
+                ("holder_name", String),
+                ("serial_number", Integer),
+                ("status_list_index", Integer),
+                ("valid_from", Integer),
+                ("valid_until", Integer),
+            ],
+            Self::DriversLicense => &[
+                ("credential_type", String),
+                ("issuing_jurisdiction", String),
+                ("holder_name", String),
+                ("serial_number", Integer),
+                ("home_street", String),
+                ("home_city", String),
+                ("home_region", String),
+                ("home_postal_code", String),
+                ("home_country", String),
+                ("organ_donor", Boolean),
+                ("birthdate", Integer),
+                ("age_over_18", Boolean),
+                ("age_over_21", Boolean),
+                ("age_over_65", Boolean),
+                ("status_list_index", Integer),
+                ("valid_from", Integer),
+                ("valid_until", Integer),
+            ],
+        }
+    }
+
+    /// The name of the attribute held in the BBS message at the index, or `message_<index>` if this
+    /// type of credential has no such message.
+    pub fn attribute_name(&self, index: usize) -> String {
+        match self.attributes().get(index) {
+            Some((name, _)) => name.to_string(),
+            None => format!("message_{index}"),
+        }
+    }
+
+    /// Index of the BBS message holding the named attribute, if this type of credential has it.
+    pub fn attribute_index(&self, name: &str) -> Option<usize> {
+        self.attributes()
+            .iter()
+            .position(|(attribute, _)| *attribute == name)
+    }
+
+    /// Decode a credential of this type and construct the vector of BBS messages to sign, verify or
+    /// prove for it. This is the canonical encoding of credentials into messages: all actors must
+    /// use it so that they agree on message order.
+    pub fn bbs_messages(&self, encoded_credential: &str) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        match self {
+            Self::LibraryCard => {
+                let library_card: LibraryCard = serde_json::from_str(encoded_credential)
+                    .context("failed to decode library card")?;
+                Ok(library_card.bbs_messages())
+            }
+            Self::DriversLicense => {
+                let drivers_license: DriversLicense = serde_json::from_str(encoded_credential)
+                    .context("failed to decode driver's license")?;
+                Ok(drivers_license.bbs_messages())
+            }
+        }
+    }
+}
+
+/// How a credential attribute is encoded into a BBS message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeEncoding {
+    /// UTF-8 text.
+    String,
+    /// A big endian u64.
+    Integer,
+    /// A single byte, 0 or 1.
+    Boolean,
+}
+
+impl AttributeEncoding {
+    /// Decode a message into a human readable value, or `None` if it isn't validly encoded.
+    pub fn decode(&self, message: &[u8]) -> Option<String> {
+        match self {
+            Self::String => String::from_utf8(message.to_vec()).ok(),
+            Self::Integer => message
+                .try_into()
+                .ok()
+                .map(|bytes| u64::from_be_bytes(bytes).to_string()),
+            Self::Boolean => match message {
+                [0] => Some("false".to_string()),
+                [1] => Some("true".to_string()),
+                _ => None,
+            },
+        }
+    }
+
+    /// Encode a human readable value, as [`Self::decode`] yields it, into a message, or `None` if it
+    /// isn't a valid value.
+    pub fn encode(&self, value: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::String => Some(value.as_bytes().to_vec()),
+            Self::Integer => value
+                .parse::<u64>()
+                .ok()
+                .map(|integer| integer.to_be_bytes().to_vec()),
+            Self::Boolean => match value {
+                "false" => Some(Vec::from([0])),
+                "true" => Some(Vec::from([1])),
+                _ => None,
+            },
+        }
+    }
+}
+
 /// An issued credential.
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Credential {
@@ -22,11 +204,34 @@ pub struct Credential {
 }
 
 impl Credential {
-    pub fn bbs_messages(&self) -> Vec<Vec<u8>> {
-        todo!("construct vector of BBS messages to sign, verify or prove for this credential")
+    /// Decode the credential and construct the vector of BBS messages to sign, verify or prove for
+    /// it. See [`CredentialType::bbs_messages`].
+    pub fn bbs_messages(&self) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        self.credential_type.bbs_messages(&self.encoded_credential)
+    }
+
+    /// Whether the credential is valid at the time, in seconds since the UNIX epoch: that is, no
+    /// earlier than its `valid_from` attribute and before its `valid_until` attribute.
+    pub fn valid_at(&self, time: u64) -> Result<bool, anyhow::Error> {
+        Ok(self.integer_attribute("valid_from")? <= time
+            && time < self.integer_attribute("valid_until")?)
+    }
+
+    /// The value of the credential's integer attribute with the name, e.g. `serial_number`.
+    pub fn integer_attribute(&self, name: &str) -> Result<u64, anyhow::Error> {
+        let messages = self.bbs_messages()?;
+        self.credential_type
+            .attribute_index(name)
+            .and_then(|index| messages.get(index))
+            .and_then(|message| <[u8; 8]>::try_from(message.as_slice()).ok())
+            .map(u64::from_be_bytes)
+            .ok_or_else(|| anyhow!("credential has no {name}"))
     }
 }
 
+/// The `valid_until` attribute of credentials that never expire.
+pub const NEVER_EXPIRES: u64 = u64::MAX;
+
 /// A signature over an issued credential.
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct CredentialSignature {
@@ -34,6 +239,8 @@ pub struct CredentialSignature {
     pub signature: Vec<u8>,
     /// The header of the signature (not to be confused with presentation_header).
     pub header: Vec<u8>,
+    /// Identifies which of the issuer's keys made the signature.
+    pub key_id: String,
 }
 
 /// A library card.
@@ -42,6 +249,27 @@ pub struct LibraryCard {
     pub library_name: String,
     pub holder_name: String,
     pub serial_number: u64,
+    /// Index of this card in the library's status list.
+    pub status_list_index: u64,
+    /// When the card becomes valid, in seconds since the UNIX epoch.
+    pub valid_from: u64,
+    /// When the card expires, in seconds since the UNIX epoch, or [`NEVER_EXPIRES`].
+    pub valid_until: u64,
+}
+
+impl LibraryCard {
+    /// Construct the vector of BBS messages for this library card.
+    pub fn bbs_messages(&self) -> Vec<Vec<u8>> {
+        Vec::from([
+            CredentialType::LibraryCard.name().as_bytes().to_vec(),
+            self.library_name.clone().into_bytes(),
+            self.holder_name.clone().into_bytes(),
+            self.serial_number.to_be_bytes().to_vec(),
+            self.status_list_index.to_be_bytes().to_vec(),
+            self.valid_from.to_be_bytes().to_vec(),
+            self.valid_until.to_be_bytes().to_vec(),
+        ])
+    }
 }
 
 /// A request for a library card.
@@ -50,23 +278,483 @@ pub struct LibraryCardRequest {
     pub holder_name: String,
 }
 
+/// Ages, in years, for which driver's license issuers attest whether the holder has reached that
+/// age. These attestations let the holder prove they are over some age without disclosing their
+/// birthdate.
+pub const AGE_OVER_THRESHOLDS: [u64; 3] = [18, 21, 65];
+
+/// A postal address. Each field is signed as a separate BBS message so that a holder can disclose
+/// part of the address, such as the region they reside in, without the rest.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct Address {
+    pub street: String,
+    pub city: String,
+    /// State, province or similar subdivision of the country, e.g. `OH`.
+    pub region: String,
+    pub postal_code: String,
+    /// ISO 3166-1 alpha-2 country code, e.g. `US`.
+    pub country: String,
+}
+
+impl Address {
+    /// Construct the vector of BBS messages for this address.
+    fn bbs_messages(&self) -> [Vec<u8>; 5] {
+        [
+            self.street.clone().into_bytes(),
+            self.city.clone().into_bytes(),
+            self.region.clone().into_bytes(),
+            self.postal_code.clone().into_bytes(),
+            self.country.clone().into_bytes(),
+        ]
+    }
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}, {}, {} {}, {}",
+            self.street, self.city, self.region, self.postal_code, self.country
+        )
+    }
+}
+
 /// A driver's license.
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct DriversLicense {
     pub issuing_jurisdiction: String,
     pub holder_name: String,
     pub serial_number: u64,
-    pub home_address: String,
+    pub home_address: Address,
     pub organ_donor: bool,
     // Holder's birthdate, in seconds since the UNIX epoch.
     pub birthdate: u64,
+    /// Whether the holder had reached each age in [`AGE_OVER_THRESHOLDS`] when the license was
+    /// issued. Age only increases, so an attestation that the holder is over some age remains true.
+    pub age_over: [bool; AGE_OVER_THRESHOLDS.len()],
+    /// Index of this license in the issuer's status list.
+    pub status_list_index: u64,
+    /// When the license becomes valid, in seconds since the UNIX epoch.
+    pub valid_from: u64,
+    /// When the license expires, in seconds since the UNIX epoch, or [`NEVER_EXPIRES`].
+    pub valid_until: u64,
+}
+
+impl DriversLicense {
+    /// Index of the first age attestation message, following the birthdate.
+    const FIRST_AGE_OVER_INDEX: usize = 11;
+
+    /// Construct the vector of BBS messages for this driver's license.
+    pub fn bbs_messages(&self) -> Vec<Vec<u8>> {
+        let mut messages = Vec::from([
+            CredentialType::DriversLicense.name().as_bytes().to_vec(),
+            self.issuing_jurisdiction.clone().into_bytes(),
+            self.holder_name.clone().into_bytes(),
+            self.serial_number.to_be_bytes().to_vec(),
+        ]);
+        messages.extend(self.home_address.bbs_messages());
+        messages.extend([
+            Vec::from([u8::from(self.organ_donor)]),
+            self.birthdate.to_be_bytes().to_vec(),
+        ]);
+        messages.extend(
+            self.age_over
+                .iter()
+                .map(|age_over| Vec::from([u8::from(*age_over)])),
+        );
+        messages.extend([
+            self.status_list_index.to_be_bytes().to_vec(),
+            self.valid_from.to_be_bytes().to_vec(),
+            self.valid_until.to_be_bytes().to_vec(),
+        ]);
+
+        messages
+    }
+
+    /// Index of the BBS message attesting whether the holder is over the threshold age, or `None`
+    /// if the threshold isn't one of [`AGE_OVER_THRESHOLDS`].
+    pub fn age_over_message_index(threshold: u64) -> Option<usize> {
+        AGE_OVER_THRESHOLDS
+            .iter()
+            .position(|t| *t == threshold)
+            .map(|position| Self::FIRST_AGE_OVER_INDEX + position)
+    }
+
+    /// Whether someone born at `birthdate` had reached each age in [`AGE_OVER_THRESHOLDS`] at time
+    /// `as_of`. Both times are in seconds since the UNIX epoch.
+    pub fn age_over(birthdate: u64, as_of: u64) -> [bool; AGE_OVER_THRESHOLDS.len()] {
+        let age = age_in_years(birthdate, as_of);
+        AGE_OVER_THRESHOLDS.map(|threshold| age >= threshold)
+    }
+}
+
+/// Age in whole years at time `as_of` of someone born at `birthdate`.
+fn age_in_years(birthdate: u64, as_of: u64) -> u64 {
+    if as_of < birthdate {
+        return 0;
+    }
+
+    let (birth_year, birth_month, birth_day) = civil_from_unix(birthdate);
+    let (year, month, day) = civil_from_unix(as_of);
+
+    let age = year - birth_year;
+    if (month, day) < (birth_month, birth_day) {
+        age - 1
+    } else {
+        age
+    }
+}
+
+/// Convert seconds since the UNIX epoch to a (year, month, day) date in UTC.
+// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_unix(seconds: u64) -> (u64, u64, u64) {
+    let days = seconds / 86400 + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
 }
 
 /// A request for a driver's license.
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct DriversLicenseRequest {
     pub holder_name: String,
-    pub home_address: String,
+    pub home_address: Address,
     pub organ_donor: bool,
     pub birthdate: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Address, AttributeEncoding, Credential, CredentialSignature, CredentialType,
+        DriversLicense, LibraryCard, civil_from_unix,
+    };
+
+    fn evergreen_terrace() -> Address {
+        Address {
+            street: "742 Evergreen Terrace".to_string(),
+            city: "Springfield".to_string(),
+            region: "OH".to_string(),
+            postal_code: "45501".to_string(),
+            country: "US".to_string(),
+        }
+    }
+
+    #[test]
+    fn library_card_messages() {
+        let library_card = LibraryCard {
+            library_name: "library-1".to_string(),
+            holder_name: "Homer Simpson".to_string(),
+            serial_number: 7,
+            status_list_index: 12,
+            valid_from: 100,
+            valid_until: 200,
+        };
+        let credential = Credential {
+            credential_type: CredentialType::LibraryCard,
+            encoded_credential: serde_json::to_string(&library_card).unwrap(),
+            signature: CredentialSignature {
+                signature: Vec::new(),
+                header: Vec::new(),
+                key_id: String::new(),
+            },
+        };
+
+        assert_eq!(
+            credential.bbs_messages().unwrap(),
+            Vec::from([
+                b"LibraryCard".to_vec(),
+                b"library-1".to_vec(),
+                b"Homer Simpson".to_vec(),
+                7u64.to_be_bytes().to_vec(),
+                12u64.to_be_bytes().to_vec(),
+                100u64.to_be_bytes().to_vec(),
+                200u64.to_be_bytes().to_vec(),
+            ])
+        );
+        assert!(!credential.valid_at(99).unwrap());
+        assert!(credential.valid_at(100).unwrap());
+        assert!(credential.valid_at(199).unwrap());
+        assert!(!credential.valid_at(200).unwrap());
+    }
+
+    #[test]
+    fn status_message_index() {
+        let library_card = LibraryCard {
+            library_name: "library-1".to_string(),
+            holder_name: "Homer Simpson".to_string(),
+            serial_number: 7,
+            status_list_index: 12,
+            valid_from: 100,
+            valid_until: 200,
+        };
+        assert_eq!(
+            library_card.bbs_messages()[CredentialType::LibraryCard.status_message_index()],
+            12u64.to_be_bytes()
+        );
+
+        let drivers_license = DriversLicense {
+            issuing_jurisdiction: "dmv-1".to_string(),
+            holder_name: "Homer Simpson".to_string(),
+            serial_number: 1,
+            home_address: evergreen_terrace(),
+            organ_donor: true,
+            birthdate: 1753729603,
+            age_over: [true, false, false],
+            status_list_index: 13,
+            valid_from: 100,
+            valid_until: 200,
+        };
+        assert_eq!(
+            drivers_license.bbs_messages()[CredentialType::DriversLicense.status_message_index()],
+            13u64.to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let drivers_license = DriversLicense {
+            issuing_jurisdiction: "dmv-1".to_string(),
+            holder_name: "Homer Simpson".to_string(),
+            serial_number: 1,
+            home_address: evergreen_terrace(),
+            organ_donor: true,
+            birthdate: 1753729603,
+            age_over: [true, false, false],
+            status_list_index: 13,
+            valid_from: 100,
+            valid_until: 200,
+        };
+        let attributes: Vec<_> = CredentialType::DriversLicense
+            .attributes()
+            .iter()
+            .zip(drivers_license.bbs_messages())
+            .map(|((name, encoding), message)| (*name, encoding.decode(&message).unwrap()))
+            .collect();
+        assert_eq!(
+            attributes,
+            [
+                ("credential_type", "DriversLicense"),
+                ("issuing_jurisdiction", "dmv-1"),
+                ("holder_name", "Homer Simpson"),
+                ("serial_number", "1"),
+                ("home_street", "742 Evergreen Terrace"),
+                ("home_city", "Springfield"),
+                ("home_region", "OH"),
+                ("home_postal_code", "45501"),
+                ("home_country", "US"),
+                ("organ_donor", "true"),
+                ("birthdate", "1753729603"),
+                ("age_over_18", "true"),
+                ("age_over_21", "false"),
+                ("age_over_65", "false"),
+                ("status_list_index", "13"),
+                ("valid_from", "100"),
+                ("valid_until", "200"),
+            ]
+            .map(|(name, value)| (name, value.to_string()))
+        );
+
+        for credential_type in [CredentialType::LibraryCard, CredentialType::DriversLicense] {
+            for name in CredentialType::RESERVED_ATTRIBUTES {
+                assert!(credential_type.attribute_index(name).is_some());
+            }
+            assert_eq!(
+                credential_type.attributes()[CredentialType::MESSAGE_INDEX].0,
+                "credential_type"
+            );
+            assert_eq!(
+                credential_type.attributes()[credential_type.status_message_index()].0,
+                "status_list_index"
+            );
+            assert_eq!(
+                credential_type.attributes()[credential_type.valid_from_message_index()].0,
+                "valid_from"
+            );
+            assert_eq!(
+                credential_type.attributes()[credential_type.valid_until_message_index()].0,
+                "valid_until"
+            );
+        }
+        assert_eq!(
+            CredentialType::LibraryCard.attributes().len(),
+            LibraryCard {
+                library_name: String::new(),
+                holder_name: String::new(),
+                serial_number: 0,
+                status_list_index: 0,
+                valid_from: 100,
+                valid_until: 200,
+            }
+            .bbs_messages()
+            .len()
+        );
+
+        assert_eq!(AttributeEncoding::Boolean.decode(&[2]), None);
+        assert_eq!(AttributeEncoding::Integer.decode(&[1]), None);
+
+        assert_eq!(
+            CredentialType::DriversLicense.attribute_index("age_over_21"),
+            DriversLicense::age_over_message_index(21)
+        );
+        assert_eq!(
+            CredentialType::LibraryCard.attribute_index("birthdate"),
+            None
+        );
+        for (encoding, value) in [
+            (AttributeEncoding::String, "Homer Simpson"),
+            (AttributeEncoding::Integer, "13"),
+            (AttributeEncoding::Boolean, "true"),
+        ] {
+            assert_eq!(
+                encoding.decode(&encoding.encode(value).unwrap()).unwrap(),
+                value
+            );
+        }
+        assert_eq!(AttributeEncoding::Boolean.encode("yes"), None);
+    }
+
+    #[test]
+    fn drivers_license_messages() {
+        let drivers_license = DriversLicense {
+            issuing_jurisdiction: "dmv-1".to_string(),
+            holder_name: "Homer Simpson".to_string(),
+            serial_number: 1,
+            home_address: evergreen_terrace(),
+            organ_donor: true,
+            birthdate: 1753729603,
+            age_over: [true, false, false],
+            status_list_index: 12,
+            valid_from: 100,
+            valid_until: 200,
+        };
+        let credential = Credential {
+            credential_type: CredentialType::DriversLicense,
+            encoded_credential: serde_json::to_string(&drivers_license).unwrap(),
+            signature: CredentialSignature {
+                signature: Vec::new(),
+                header: Vec::new(),
+                key_id: String::new(),
+            },
+        };
+
+        assert_eq!(
+            credential.bbs_messages().unwrap(),
+            Vec::from([
+                b"DriversLicense".to_vec(),
+                b"dmv-1".to_vec(),
+                b"Homer Simpson".to_vec(),
+                1u64.to_be_bytes().to_vec(),
+                b"742 Evergreen Terrace".to_vec(),
+                b"Springfield".to_vec(),
+                b"OH".to_vec(),
+                b"45501".to_vec(),
+                b"US".to_vec(),
+                Vec::from([1]),
+                1753729603u64.to_be_bytes().to_vec(),
+                Vec::from([1]),
+                Vec::from([0]),
+                Vec::from([0]),
+                12u64.to_be_bytes().to_vec(),
+                100u64.to_be_bytes().to_vec(),
+                200u64.to_be_bytes().to_vec(),
+            ])
+        );
+        assert_eq!(DriversLicense::age_over_message_index(18), Some(11));
+        assert_eq!(DriversLicense::age_over_message_index(65), Some(13));
+        assert_eq!(DriversLicense::age_over_message_index(19), None);
+    }
+
+    #[test]
+    fn age_over() {
+        // 1980-05-12T00:00:00Z
+        let birthdate = 326937600;
+        // 2001-05-11T23:59:59Z
+        let day_before_21st_birthday = 989625599;
+        // 2001-05-12T00:00:00Z
+        let twenty_first_birthday = 989625600;
+
+        assert_eq!(civil_from_unix(birthdate), (1980, 5, 12));
+        assert_eq!(civil_from_unix(twenty_first_birthday), (2001, 5, 12));
+        assert_eq!(
+            DriversLicense::age_over(birthdate, day_before_21st_birthday),
+            [true, false, false]
+        );
+        assert_eq!(
+            DriversLicense::age_over(birthdate, twenty_first_birthday),
+            [true, true, false]
+        );
+        assert_eq!(
+            DriversLicense::age_over(twenty_first_birthday, birthdate),
+            [false, false, false]
+        );
+    }
+
+    #[test]
+    fn mismatched_credential_type() {
+        let library_card = LibraryCard {
+            library_name: "library-1".to_string(),
+            holder_name: "Homer Simpson".to_string(),
+            serial_number: 7,
+            status_list_index: 12,
+            valid_from: 100,
+            valid_until: 200,
+        };
+        let credential = Credential {
+            credential_type: CredentialType::DriversLicense,
+            encoded_credential: serde_json::to_string(&library_card).unwrap(),
+            signature: CredentialSignature {
+                signature: Vec::new(),
+                header: Vec::new(),
+                key_id: String::new(),
+            },
+        };
+
+        credential.bbs_messages().unwrap_err();
+    }
+
+    #[test]
+    fn credential_type_from_disclosed_messages() {
+        let drivers_license = DriversLicense {
+            issuing_jurisdiction: "dmv-1".to_string(),
+            holder_name: "Homer Simpson".to_string(),
+            serial_number: 1,
+            home_address: evergreen_terrace(),
+            organ_donor: true,
+            birthdate: 1753729603,
+            age_over: [true, false, false],
+            status_list_index: 12,
+            valid_from: 100,
+            valid_until: 200,
+        };
+        let disclosed_messages: Vec<_> = drivers_license
+            .bbs_messages()
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| [CredentialType::MESSAGE_INDEX, 2].contains(index))
+            .collect();
+        assert_eq!(
+            CredentialType::from_disclosed_messages(&disclosed_messages).unwrap(),
+            CredentialType::DriversLicense
+        );
+
+        CredentialType::from_disclosed_messages(&disclosed_messages[1..]).unwrap_err();
+        CredentialType::from_disclosed_messages(&[(
+            CredentialType::MESSAGE_INDEX,
+            b"Passport".to_vec(),
+        )])
+        .unwrap_err();
+    }
+}
@@ -0,0 +1,178 @@
+//! Errors that actors report to one another in HTTP responses.
+
+use axum::http::StatusCode;
+use reqwest::{RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// An error an actor can report to whoever sent it a request. Serialized as a JSON object whose
+/// `code` field is a stable, machine readable identifier for the variant.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum Error {
+    /// The request was malformed.
+    BadRequest { message: String },
+    /// The actor is not permitted to do what was requested, like issuing a type of credential it
+    /// isn't configured to issue.
+    NotPermitted { message: String },
+    /// Something the request refers to, like a credential serial number, doesn't exist.
+    NotFound { message: String },
+    /// The wallet holds no credential that can satisfy a proof request.
+    NoMatchingCredential { message: String },
+    /// A credential or proof was issued by an issuer the actor doesn't trust.
+    UntrustedIssuer { message: String },
+    /// A credential or proof was issued by an issuer that no jurisdiction the actor trusts
+    /// accredited to issue that type of credential.
+    UnaccreditedIssuer { message: String },
+    /// An issuer was asked to issue a credential into a wallet that wasn't made by a vendor it
+    /// trusts.
+    UntrustedWallet { message: String },
+    /// A signature or proof failed to verify.
+    InvalidSignature { message: String },
+    /// A request the actor made to another actor failed.
+    Upstream {
+        message: String,
+
+        /// The host the request was sent to.
+        host: String,
+
+        /// The error the other actor responded with, if it responded with one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<Box<Error>>,
+    },
+    /// Anything else.
+    Internal { message: String },
+}
+
+impl Error {
+    /// The HTTP status with which this error is reported.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::BadRequest { .. } => StatusCode::BAD_REQUEST,
+            Self::NotPermitted { .. }
+            | Self::UntrustedIssuer { .. }
+            | Self::UnaccreditedIssuer { .. }
+            | Self::UntrustedWallet { .. } => StatusCode::FORBIDDEN,
+            Self::NotFound { .. } | Self::NoMatchingCredential { .. } => StatusCode::NOT_FOUND,
+            Self::InvalidSignature { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Upstream { .. } => StatusCode::BAD_GATEWAY,
+            Self::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// The stable, machine readable identifier for the variant, as it appears in the `code` field
+    /// of the serialized error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::BadRequest { .. } => "bad_request",
+            Self::NotPermitted { .. } => "not_permitted",
+            Self::NotFound { .. } => "not_found",
+            Self::NoMatchingCredential { .. } => "no_matching_credential",
+            Self::UntrustedIssuer { .. } => "untrusted_issuer",
+            Self::UnaccreditedIssuer { .. } => "unaccredited_issuer",
+            Self::UntrustedWallet { .. } => "untrusted_wallet",
+            Self::InvalidSignature { .. } => "invalid_signature",
+            Self::Upstream { .. } => "upstream",
+            Self::Internal { .. } => "internal",
+        }
+    }
+
+    /// The error at the root of a chain of upstream errors: the error reported by the last actor
+    /// that reported a typed error at all.
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::Upstream {
+                error: Some(error), ..
+            } => error.root_cause(),
+            _ => self,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BadRequest { message }
+            | Self::NotPermitted { message }
+            | Self::NotFound { message }
+            | Self::NoMatchingCredential { message }
+            | Self::UntrustedIssuer { message }
+            | Self::UnaccreditedIssuer { message }
+            | Self::UntrustedWallet { message }
+            | Self::InvalidSignature { message }
+            | Self::Internal { message } => write!(f, "{message}"),
+            Self::Upstream {
+                message,
+                error: Some(error),
+                ..
+            } => write!(f, "{message}: {error}"),
+            Self::Upstream { message, .. } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Send a request to another actor. If the request can't be sent or the actor responds with an
+/// error, fails with [`Error::Upstream`], carrying the actor's error if it reported one.
+pub async fn send(host: &str, request: RequestBuilder) -> Result<Response, Error> {
+    let response = request.send().await.map_err(|error| Error::Upstream {
+        message: format!("failed to send request to {host}: {error}"),
+        host: host.to_string(),
+        error: None,
+    })?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    Err(Error::Upstream {
+        message: format!("request to {host} failed with status {status}"),
+        host: host.to_string(),
+        // Not every actor is necessarily one of ours, so the body might not be a typed error.
+        error: response.json().await.ok().map(Box::new),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use axum::http::StatusCode;
+    use serde_json::json;
+
+    #[test]
+    fn serialization() {
+        let error = Error::Upstream {
+            message: "request to issuer-dmv failed".to_string(),
+            host: "issuer-dmv".to_string(),
+            error: Some(Box::new(Error::NotPermitted {
+                message: "not permitted to issue credential LibraryCard".to_string(),
+            })),
+        };
+
+        let encoded = serde_json::to_value(&error).unwrap();
+        assert_eq!(
+            encoded,
+            json!({
+                "code": "upstream",
+                "message": "request to issuer-dmv failed",
+                "host": "issuer-dmv",
+                "error": {
+                    "code": "not_permitted",
+                    "message": "not permitted to issue credential LibraryCard",
+                },
+            })
+        );
+        assert_eq!(serde_json::from_value::<Error>(encoded).unwrap(), error);
+
+        assert_eq!(error.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(error.root_cause().status(), StatusCode::FORBIDDEN);
+        assert_eq!(error.code(), "upstream");
+        assert_eq!(error.root_cause().code(), "not_permitted");
+        assert_eq!(
+            error.to_string(),
+            "request to issuer-dmv failed: not permitted to issue credential LibraryCard"
+        );
+    }
+}
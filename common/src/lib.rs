@@ -1,7 +1,15 @@
 //! Items common to all actors in a digital identity simulation.
 
+pub mod accreditation;
+pub mod attestation;
 pub mod bbs;
 pub mod config;
 pub mod credential;
+pub mod error;
+pub mod linkability;
 pub mod messages;
+pub mod revocation;
 pub mod router;
+pub mod storage;
+pub mod time;
+pub mod trust;
@@ -0,0 +1,451 @@
+//! Analysis of whether relying parties could link the proofs presented to them, were they to
+//! collude.
+//!
+//! BBS proofs are meant to be unlinkable: two proofs derived from the same credential should look
+//! no more alike than proofs derived from two different credentials. But what a proof discloses can
+//! still link it to others. Colluding relying parties pool the presentations made to them, and can
+//! be certain that two of them were made by the same person if they share some feature, like a
+//! disclosed message, that no presentation by anybody else shares. A presentation may also be
+//! linkable by everything it discloses taken together, even when no single disclosed message is
+//! unique to its person.
+//!
+//! Whether anybody else shares a feature depends on the population: in a population of one person,
+//! every presentation is trivially linkable. The analysis is only meaningful across presentations
+//! by several persons, and it needs to know who made each presentation, which the relying parties
+//! themselves don't.
+
+use crate::{credential::CredentialType, messages::person::ProofType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+/// A proof presented to a relying party.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Presentation {
+    /// The person who presented the proof.
+    pub person: String,
+
+    /// The relying party it was presented to.
+    pub relying_party: String,
+
+    /// The type of proof.
+    pub proof_type: ProofType,
+
+    /// The type of credential the proof was made from.
+    pub credential_type: CredentialType,
+
+    /// The header from the BBS signature in the proof.
+    pub header: Vec<u8>,
+
+    /// The BBS proof.
+    pub proof: Vec<u8>,
+
+    /// Messages disclosed in the proof. Tuple of message index and message.
+    pub disclosed_messages: Vec<(usize, Vec<u8>)>,
+
+    /// When the proof was presented, in seconds since the UNIX epoch.
+    pub presented_at: u64,
+}
+
+impl Presentation {
+    /// Features of this presentation, and their values, that are equal in any presentation they
+    /// link it to. Timing is not among them, since presentations close together in time are linked
+    /// by it.
+    fn features(&self) -> Vec<(LinkingFeature, Vec<&[u8]>)> {
+        let credential_type = self.credential_type;
+
+        let mut features = vec![
+            (LinkingFeature::Header, vec![self.header.as_slice()]),
+            (LinkingFeature::Proof, vec![self.proof.as_slice()]),
+        ];
+        features.extend(self.disclosed_messages.iter().map(|(index, message)| {
+            (
+                LinkingFeature::DisclosedMessage {
+                    name: credential_type.attribute_name(*index),
+                },
+                vec![message.as_slice()],
+            )
+        }));
+
+        if !self.disclosed_messages.is_empty() {
+            let mut disclosed_messages: Vec<_> = self.disclosed_messages.iter().collect();
+            disclosed_messages.sort_by_key(|(index, _)| *index);
+            features.push((
+                LinkingFeature::Combination {
+                    names: disclosed_messages
+                        .iter()
+                        .map(|(index, _)| credential_type.attribute_name(*index))
+                        .collect(),
+                },
+                std::iter::once(self.header.as_slice())
+                    .chain(
+                        disclosed_messages
+                            .iter()
+                            .map(|(_, message)| message.as_slice()),
+                    )
+                    .collect(),
+            ));
+        }
+
+        features
+    }
+}
+
+/// Something colluding relying parties could link presentations by.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LinkingFeature {
+    /// The header of the signature, which names the credential's issuer.
+    Header,
+    /// A disclosed message, named as in [`crate::credential::CredentialType::attributes`].
+    DisclosedMessage { name: String },
+    /// The header together with all the disclosed messages, named as for
+    /// [`LinkingFeature::DisclosedMessage`]. Only reported when neither the header nor any of
+    /// the messages links presentations on its own.
+    Combination { names: Vec<String> },
+    /// The proof itself. BBS proofs are randomized, so this should never link anything.
+    Proof,
+    /// The time at which presentations were made.
+    Timing,
+}
+
+/// How linkable the presentations of some type of proof to some relying party are.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProofTypeLinkability {
+    /// The relying party the proofs were presented to.
+    pub relying_party: String,
+
+    /// The type of proof.
+    pub proof_type: ProofType,
+
+    /// Number of presentations of this type to this relying party.
+    pub presentations: usize,
+
+    /// Number of those presentations that could be linked to a presentation made to some other
+    /// relying party by the same person.
+    pub linked_presentations: usize,
+
+    /// Features by which any of those presentations could be linked.
+    pub linked_by: BTreeSet<LinkingFeature>,
+}
+
+impl ProofTypeLinkability {
+    /// Whether any presentation of this type could be linked.
+    pub fn linkable(&self) -> bool {
+        self.linked_presentations > 0
+    }
+}
+
+/// Analysis of which presentations colluding relying parties could link.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LinkabilityReport {
+    /// Number of persons who presented proofs.
+    pub persons: usize,
+
+    /// Linkability of each type of proof presented to each relying party, in the order they were
+    /// first presented.
+    pub proof_types: Vec<ProofTypeLinkability>,
+}
+
+impl LinkabilityReport {
+    /// Work out which presentations could be linked to presentations made to other relying parties.
+    /// Presentations made within `timing_window` seconds of each other are considered close enough
+    /// in time to be linked by timing.
+    pub fn analyze(presentations: &[Presentation], timing_window: u64) -> Self {
+        let features: Vec<_> = presentations
+            .iter()
+            .map(|presentation| presentation.features())
+            .collect();
+
+        let mut report = Self {
+            persons: presentations
+                .iter()
+                .map(|presentation| presentation.person.as_str())
+                .collect::<HashSet<_>>()
+                .len(),
+            proof_types: Vec::new(),
+        };
+
+        for (presentation, presentation_features) in presentations.iter().zip(&features) {
+            // Each feature links the presentation to the presentations that share it.
+            let mut candidates: Vec<_> = presentation_features
+                .iter()
+                .map(|feature| {
+                    let sharing: Vec<_> = presentations
+                        .iter()
+                        .zip(&features)
+                        .filter(|(_, other_features)| other_features.contains(feature))
+                        .map(|(other, _)| other)
+                        .collect();
+                    (feature.0.clone(), sharing)
+                })
+                .collect();
+            candidates.push((
+                LinkingFeature::Timing,
+                presentations
+                    .iter()
+                    .filter(|other| {
+                        other.presented_at.abs_diff(presentation.presented_at) <= timing_window
+                    })
+                    .collect(),
+            ));
+
+            // The link is certain only if nobody else's presentation shares the feature.
+            let mut linked_by: BTreeSet<_> = candidates
+                .into_iter()
+                .filter(|(_, sharing)| {
+                    sharing
+                        .iter()
+                        .any(|other| other.relying_party != presentation.relying_party)
+                        && sharing
+                            .iter()
+                            .all(|other| other.person == presentation.person)
+                })
+                .map(|(feature, _)| feature)
+                .collect();
+            // If any part of the combination is unique to the person, so is the combination.
+            if linked_by.iter().any(|feature| {
+                matches!(
+                    feature,
+                    LinkingFeature::Header | LinkingFeature::DisclosedMessage { .. }
+                )
+            }) {
+                linked_by.retain(|feature| !matches!(feature, LinkingFeature::Combination { .. }));
+            }
+
+            let index = match report.proof_types.iter().position(|linkability| {
+                linkability.relying_party == presentation.relying_party
+                    && linkability.proof_type == presentation.proof_type
+            }) {
+                Some(index) => index,
+                None => {
+                    report.proof_types.push(ProofTypeLinkability {
+                        relying_party: presentation.relying_party.clone(),
+                        proof_type: presentation.proof_type.clone(),
+                        presentations: 0,
+                        linked_presentations: 0,
+                        linked_by: BTreeSet::new(),
+                    });
+                    report.proof_types.len() - 1
+                }
+            };
+            let linkability = &mut report.proof_types[index];
+            linkability.presentations += 1;
+            if !linked_by.is_empty() {
+                linkability.linked_presentations += 1;
+            }
+            linkability.linked_by.extend(linked_by);
+        }
+
+        report
+    }
+
+    /// Linkability of the type of proof presented to the relying party, if any were.
+    pub fn get(
+        &self,
+        relying_party: &str,
+        proof_type: &ProofType,
+    ) -> Option<&ProofTypeLinkability> {
+        self.proof_types.iter().find(|linkability| {
+            linkability.relying_party == relying_party && &linkability.proof_type == proof_type
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::person::AttributeSelection;
+
+    fn presentation(
+        person: &str,
+        relying_party: &str,
+        proof_type: ProofType,
+        disclosed_messages: Vec<(usize, Vec<u8>)>,
+        presented_at: u64,
+    ) -> Presentation {
+        Presentation {
+            person: person.to_string(),
+            relying_party: relying_party.to_string(),
+            proof_type,
+            credential_type: CredentialType::DriversLicense,
+            header: b"issuer/dmv-1".to_vec(),
+            proof: rand::random::<[u8; 16]>().to_vec(),
+            disclosed_messages,
+            presented_at,
+        }
+    }
+
+    #[test]
+    fn linkability() {
+        let age_over_21 = ProofType::AgeOver { threshold: 21 };
+        let presentations = [
+            // Both persons are over 21, so that alone links nothing.
+            presentation(
+                "homer",
+                "bar",
+                age_over_21.clone(),
+                vec![(12, vec![1])],
+                100,
+            ),
+            presentation(
+                "marge",
+                "bar",
+                age_over_21.clone(),
+                vec![(12, vec![1])],
+                200,
+            ),
+            presentation(
+                "homer",
+                "club",
+                age_over_21.clone(),
+                vec![(12, vec![1])],
+                110,
+            ),
+            presentation(
+                "marge",
+                "club",
+                age_over_21.clone(),
+                vec![(12, vec![1])],
+                900,
+            ),
+            // Serial numbers are unique to a credential.
+            presentation(
+                "homer",
+                "bar",
+                ProofType::HoldsDriversLicense,
+                vec![(3, 1u64.to_be_bytes().to_vec())],
+                100,
+            ),
+            presentation(
+                "marge",
+                "bar",
+                ProofType::HoldsDriversLicense,
+                vec![(3, 2u64.to_be_bytes().to_vec())],
+                200,
+            ),
+            presentation(
+                "homer",
+                "club",
+                ProofType::HoldsDriversLicense,
+                vec![(3, 1u64.to_be_bytes().to_vec())],
+                110,
+            ),
+            presentation(
+                "marge",
+                "club",
+                ProofType::HoldsDriversLicense,
+                vec![(3, 2u64.to_be_bytes().to_vec())],
+                900,
+            ),
+        ];
+
+        let report = LinkabilityReport::analyze(&presentations, 30);
+        assert_eq!(report.persons, 2);
+        assert_eq!(report.proof_types.len(), 4);
+
+        // Homer's presentations to the bar and the club were close enough in time to link.
+        let linkability = report.get("bar", &age_over_21).unwrap();
+        assert_eq!(linkability.presentations, 2);
+        assert_eq!(linkability.linked_presentations, 1);
+        assert_eq!(
+            linkability.linked_by,
+            BTreeSet::from([LinkingFeature::Timing])
+        );
+
+        let linkability = report.get("club", &ProofType::HoldsDriversLicense).unwrap();
+        assert_eq!(linkability.presentations, 2);
+        assert_eq!(linkability.linked_presentations, 2);
+        assert_eq!(
+            linkability.linked_by,
+            BTreeSet::from([
+                LinkingFeature::DisclosedMessage {
+                    name: "serial_number".to_string()
+                },
+                LinkingFeature::Timing,
+            ])
+        );
+
+        // Nothing is linkable with a timing window too short to link anything, except by serial
+        // number.
+        let report = LinkabilityReport::analyze(&presentations, 0);
+        assert!(!report.get("bar", &age_over_21).unwrap().linkable());
+        assert!(!report.get("club", &age_over_21).unwrap().linkable());
+        assert_eq!(
+            report
+                .get("bar", &ProofType::HoldsDriversLicense)
+                .unwrap()
+                .linked_presentations,
+            2
+        );
+
+        // In a population of one, everything is linkable.
+        let report =
+            LinkabilityReport::analyze(&[presentations[0].clone(), presentations[2].clone()], 0);
+        assert!(report.get("bar", &age_over_21).unwrap().linkable());
+        assert!(
+            report
+                .get("bar", &age_over_21)
+                .unwrap()
+                .linked_by
+                .contains(&LinkingFeature::Header)
+        );
+    }
+
+    #[test]
+    fn linkability_by_combination() {
+        let region = ProofType::Attributes(AttributeSelection {
+            disclose: Vec::from(["home_region".to_string()]),
+            ..Default::default()
+        });
+        let ohio = vec![(6, b"OH".to_vec())];
+        let illinois = vec![(6, b"IL".to_vec())];
+
+        // Every issuer has licensed somebody in each region, but only one person in each region
+        // holds a license from each issuer.
+        let mut presentations = Vec::new();
+        for (person, header, disclosed_messages) in [
+            ("homer", b"issuer/dmv-1", &ohio),
+            ("marge", b"issuer/dmv-1", &illinois),
+            ("bart", b"issuer/dmv-2", &ohio),
+            ("lisa", b"issuer/dmv-2", &illinois),
+        ] {
+            for (relying_party, presented_at) in [("bar", 100), ("club", 900)] {
+                presentations.push(Presentation {
+                    header: header.to_vec(),
+                    ..presentation(
+                        person,
+                        relying_party,
+                        region.clone(),
+                        disclosed_messages.clone(),
+                        presented_at + presentations.len() as u64,
+                    )
+                });
+            }
+        }
+
+        let report = LinkabilityReport::analyze(&presentations, 0);
+        let linkability = report.get("bar", &region).unwrap();
+        assert_eq!(linkability.presentations, 4);
+        assert_eq!(linkability.linked_presentations, 4);
+        assert_eq!(
+            linkability.linked_by,
+            BTreeSet::from([LinkingFeature::Combination {
+                names: Vec::from(["home_region".to_string()]),
+            }])
+        );
+
+        // Once somebody else holds a license from the same issuer in the same region, the
+        // combination no longer links anybody.
+        presentations.extend(
+            presentations
+                .clone()
+                .into_iter()
+                .map(|presentation| Presentation {
+                    person: format!("{}-twin", presentation.person),
+                    ..presentation
+                }),
+        );
+        let report = LinkabilityReport::analyze(&presentations, 0);
+        assert!(!report.get("bar", &region).unwrap().linkable());
+    }
+}
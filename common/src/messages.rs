@@ -2,7 +2,7 @@
 
 /// API objects for interacting with an issuer.
 pub mod issuer {
-    use crate::credential::CredentialType;
+    use crate::{bbs::BbsPublicKey, credential::CredentialType};
     use serde::{Deserialize, Serialize};
 
     /// A request for the issuer to issue a credential.
@@ -19,20 +19,317 @@ pub mod issuer {
         /// by the issuer receiving this request.
         pub wallet_hostname: String,
     }
+
+    /// One of an issuer's public keys, used to verify credentials it issued.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct IssuerPublicKey {
+        /// The issuer's name, as it appears in the header of its BBS signatures.
+        pub issuer: String,
+
+        /// Identifies the key among the issuer's keys. Signatures name the key that made them.
+        pub key_id: String,
+
+        /// The issuer's public key.
+        pub public_key: BbsPublicKey,
+
+        /// When the issuer started signing with the key, in seconds since the UNIX epoch.
+        pub not_before: u64,
+
+        /// When the key is retired, in seconds since the UNIX epoch, or `None` if it hasn't been
+        /// scheduled for retirement. Signatures made with a retired key are no longer valid.
+        pub not_after: Option<u64>,
+    }
+
+    impl IssuerPublicKey {
+        /// Whether signatures made with the key are valid at the time, in seconds since the UNIX
+        /// epoch.
+        pub fn valid_at(&self, time: u64) -> bool {
+            self.not_before <= time && self.not_after.is_none_or(|not_after| time < not_after)
+        }
+    }
+
+    /// A request for the issuer to start signing with a new key.
+    #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct RotateKeyRequest {
+        /// Seconds for which the key being replaced remains valid. If `None`, it remains valid
+        /// until it is explicitly retired.
+        #[serde(default)]
+        pub retire_after: Option<u64>,
+    }
+
+    /// A request for the issuer to retire one of its keys, after which credentials signed with it
+    /// no longer verify.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct RetireKeyRequest {
+        /// The key to retire.
+        pub key_id: String,
+    }
+
+    /// A request for the issuer to revoke a credential it issued.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct RevokeCredentialRequest {
+        /// Serial number of the credential to revoke.
+        pub serial_number: u64,
+    }
+
+    /// An issuer's record of a credential it issued.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct LedgerEntry {
+        /// The type of credential issued.
+        pub credential_type: CredentialType,
+
+        /// Serial number of the credential.
+        pub serial_number: u64,
+
+        /// Index of the credential in the issuer's status list.
+        pub status_list_index: u64,
+
+        /// The credential's fields, as they were issued to the holder. A JSON blob whose format is
+        /// dictated by `credential_type`.
+        pub encoded_credential: String,
+
+        /// The wallet into which the credential was issued.
+        pub wallet_hostname: String,
+
+        /// When the credential was issued, in seconds since the UNIX epoch.
+        pub issued_at: u64,
+
+        /// When the credential was revoked, in seconds since the UNIX epoch, if it was.
+        pub revoked_at: Option<u64>,
+
+        /// Serial number of the credential this one renewed, if it was issued as a renewal.
+        #[serde(default)]
+        pub supersedes: Option<u64>,
+
+        /// Serial number of the credential that renewed this one, if it was renewed.
+        #[serde(default)]
+        pub superseded_by: Option<u64>,
+
+        /// Whether the credential reached the wallet it was issued into.
+        pub delivery: Delivery,
+    }
+
+    /// Whether a credential an issuer issued reached the wallet it was issued into.
+    #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Delivery {
+        /// The issuer is delivering the credential to the wallet.
+        Pending,
+        /// The wallet accepted the credential.
+        Delivered,
+        /// The wallet didn't accept the credential, so the issuer revoked it.
+        Failed,
+    }
+
+    /// A request for the issuer to renew a credential it issued. The issuer issues a new credential,
+    /// with the requested details, into the wallet holding the old one, which it then revokes.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct RenewCredentialRequest {
+        /// Serial number of the credential to renew.
+        pub serial_number: u64,
+
+        /// The credential to issue in its place, which must be of the same type and into the same
+        /// wallet.
+        #[serde(flatten)]
+        pub issue_request: IssueCredentialRequest,
+    }
+
+    /// A query for entries in an issuer's ledger. Only entries matching every field that is set are
+    /// returned.
+    #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct LedgerQuery {
+        /// Match entries for credentials of this type.
+        pub credential_type: Option<CredentialType>,
+
+        /// Match the entry for the credential with this serial number.
+        pub serial_number: Option<u64>,
+
+        /// Match entries for credentials issued into this wallet.
+        pub wallet_hostname: Option<String>,
+    }
+
+    impl LedgerQuery {
+        /// Whether the ledger entry matches this query.
+        pub fn matches(&self, entry: &LedgerEntry) -> bool {
+            self.credential_type
+                .is_none_or(|credential_type| credential_type == entry.credential_type)
+                && self
+                    .serial_number
+                    .is_none_or(|serial_number| serial_number == entry.serial_number)
+                && self
+                    .wallet_hostname
+                    .as_ref()
+                    .is_none_or(|wallet_hostname| *wallet_hostname == entry.wallet_hostname)
+        }
+    }
+}
+
+/// API objects for interacting with a jurisdiction.
+pub mod jurisdiction {
+    use crate::bbs::BbsPublicKey;
+    use serde::{Deserialize, Serialize};
+
+    /// A jurisdiction's public key, used to verify accreditations it published.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct JurisdictionPublicKey {
+        /// The jurisdiction's name, as it appears in the header of its BBS signatures.
+        pub jurisdiction: String,
+
+        /// The jurisdiction's public key.
+        pub public_key: BbsPublicKey,
+    }
 }
 
 /// API objects for interacting with a person.
 pub mod person {
-    use crate::credential::CredentialType;
+    use crate::{credential::CredentialType, error::Error};
+    use anyhow::anyhow;
     use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, BTreeSet};
 
     /// A request for the person to prove a message.
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
     pub struct ProofRequest {
         /// The type of proof requested.
         pub proof_type: ProofType,
-        // TODO: should there be a way for the verifier to indicate what issuers it trusts?
-        // TODO: some parameters here that get folded into the presentation header?
+
+        /// Identity of the verifier requesting the proof.
+        pub verifier: String,
+
+        /// A fresh value chosen by the verifier for this request.
+        pub nonce: Vec<u8>,
+
+        /// Whether the proof must also disclose the credential's status list index, so that the
+        /// verifier can check it hasn't been revoked.
+        #[serde(default)]
+        pub revocation_check: bool,
+
+        /// Whether the proof must also disclose when the credential expires, so that the verifier
+        /// can check it hasn't. The proof doesn't disclose when the credential became valid, which
+        /// would narrow down when it was issued, so wallets make sure of that themselves.
+        #[serde(default)]
+        pub validity_check: bool,
+    }
+
+    /// Attributes disclosed in a proof so that the verifier can check that the credential is still
+    /// good, beyond those its type of proof selects.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct CheckedAttributes {
+        /// The credential's status list index, if a revocation check was requested.
+        pub status_list_index: Option<u64>,
+
+        /// When the credential expires, if a validity check was requested.
+        pub valid_until: Option<u64>,
+    }
+
+    impl ProofRequest {
+        /// The BBS presentation header to which a proof answering this request is bound. Binding
+        /// the verifier and nonce into the proof prevents it from being replayed to another
+        /// verifier or in a later session.
+        pub fn presentation_header(&self) -> Vec<u8> {
+            // Length prefix the verifier so that distinct (verifier, nonce) pairs can't collide.
+            let mut presentation_header = Vec::new();
+            presentation_header.extend_from_slice(&(self.verifier.len() as u64).to_be_bytes());
+            presentation_header.extend_from_slice(self.verifier.as_bytes());
+            presentation_header.extend_from_slice(&self.nonce);
+
+            presentation_header
+        }
+
+        /// Check that the messages disclosed in a proof answering this request, made from a
+        /// credential of the type, are those it should disclose. Returns the attributes disclosed
+        /// for the revocation and validity checks, if they were requested.
+        pub fn check_disclosed_messages(
+            &self,
+            credential_type: CredentialType,
+            disclosed_messages: &[(usize, Vec<u8>)],
+        ) -> Result<CheckedAttributes, anyhow::Error> {
+            let selection = self.selection()?;
+            let mut other_messages = disclosed_messages.to_vec();
+            // Every proof discloses the type of the credential it was made from.
+            if CredentialType::from_disclosed_messages(&other_messages)? != credential_type {
+                return Err(anyhow!(
+                    "proof should disclose credential_type {credential_type:?}"
+                ));
+            }
+            other_messages.retain(|(index, _)| *index != CredentialType::MESSAGE_INDEX);
+            let mut take = |requested: bool, index: usize, name: &str| {
+                if !requested {
+                    return Ok(None);
+                }
+                let position = other_messages
+                    .iter()
+                    .position(|(disclosed_index, _)| *disclosed_index == index)
+                    .ok_or_else(|| anyhow!("proof should disclose {name}"))?;
+                let (_, message) = other_messages.remove(position);
+                let value = message
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("malformed {name}"))?;
+                Ok::<_, anyhow::Error>(Some(u64::from_be_bytes(value)))
+            };
+
+            let checked_attributes = CheckedAttributes {
+                status_list_index: take(
+                    self.revocation_check,
+                    credential_type.status_message_index(),
+                    "status list index",
+                )?,
+                valid_until: take(
+                    self.validity_check,
+                    credential_type.valid_until_message_index(),
+                    "expiry",
+                )?,
+            };
+
+            selection.check_disclosed_messages(credential_type, &other_messages)?;
+
+            Ok(checked_attributes)
+        }
+
+        /// The selection of attributes that the request's type of proof discloses. Fails if it
+        /// selects an attribute that the request's revocation or validity check discloses anyway.
+        pub fn selection(&self) -> Result<AttributeSelection, Error> {
+            let selection = self.proof_type.selection();
+            let checked_attribute_names = self.checked_attribute_names();
+            if let Some(name) = selection
+                .attributes()
+                .find(|name| checked_attribute_names.contains(name))
+            {
+                return Err(Error::BadRequest {
+                    message: format!("{name} is disclosed by the requested checks"),
+                });
+            }
+
+            Ok(selection)
+        }
+
+        /// Names of the attributes that the request's revocation and validity checks disclose.
+        fn checked_attribute_names(&self) -> Vec<&'static str> {
+            let mut attributes = Vec::new();
+            if self.revocation_check {
+                attributes.push("status_list_index");
+            }
+            if self.validity_check {
+                attributes.push("valid_until");
+            }
+
+            attributes
+        }
+
+        /// Names of the attributes that a proof answering this request discloses, as in
+        /// [`CredentialType::attributes`].
+        pub fn disclosed_attributes(&self) -> Vec<String> {
+            let mut attributes = Vec::from(["credential_type".to_string()]);
+            attributes.extend(self.proof_type.selection().attributes().map(str::to_string));
+            attributes.extend(
+                self.checked_attribute_names()
+                    .into_iter()
+                    .map(str::to_string),
+            );
+
+            attributes
+        }
     }
 
     /// A type of proof.
@@ -44,6 +341,213 @@ pub mod person {
         HoldsLibraryCard,
         /// Proof of the holder's name (discloses a name message to verifier).
         HolderName,
+        /// Proof that the holder is at least `threshold` years old. Discloses only the driver's
+        /// license message attesting to that, and not the holder's birthdate.
+        AgeOver { threshold: u64 },
+        /// Proof that the holder's driver's license gives a home address in the country (e.g.
+        /// `US`) and, if one is given, the region (e.g. `OH`). Discloses only those parts of the
+        /// address, and not the street, city or postal code.
+        ResidesIn {
+            country: String,
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            region: Option<String>,
+        },
+        /// Proof disclosing any selection of attributes. The other types of proof are shorthands
+        /// for common selections.
+        Attributes(AttributeSelection),
+    }
+
+    impl ProofType {
+        /// The selection of attributes this type of proof discloses.
+        pub fn selection(&self) -> AttributeSelection {
+            let drivers_license = Vec::from([CredentialType::DriversLicense]);
+            match self {
+                Self::HoldsDriversLicense => AttributeSelection {
+                    credential_types: drivers_license,
+                    ..Default::default()
+                },
+                Self::HoldsLibraryCard => AttributeSelection {
+                    credential_types: Vec::from([CredentialType::LibraryCard]),
+                    ..Default::default()
+                },
+                Self::HolderName => AttributeSelection {
+                    credential_types: Vec::from([
+                        CredentialType::DriversLicense,
+                        CredentialType::LibraryCard,
+                    ]),
+                    disclose: Vec::from(["holder_name".to_string()]),
+                    ..Default::default()
+                },
+                // Disclose the issuer's attestation that the holder is over the threshold age,
+                // which is only useful if the attestation is true.
+                Self::AgeOver { threshold } => AttributeSelection {
+                    credential_types: drivers_license,
+                    require: BTreeMap::from([(
+                        format!("age_over_{threshold}"),
+                        "true".to_string(),
+                    )]),
+                    ..Default::default()
+                },
+                // Region codes are only unique within a country, so a region is always checked
+                // along with the country.
+                Self::ResidesIn { country, region } => {
+                    let mut require =
+                        BTreeMap::from([("home_country".to_string(), country.clone())]);
+                    if let Some(region) = region {
+                        require.insert("home_region".to_string(), region.clone());
+                    }
+                    AttributeSelection {
+                        credential_types: drivers_license,
+                        require,
+                        ..Default::default()
+                    }
+                }
+                Self::Attributes(selection) => selection.clone(),
+            }
+        }
+
+        /// Check that the messages disclosed in a proof made from a credential of the type are
+        /// those this type of proof should disclose. This is in addition to verifying the proof
+        /// itself.
+        pub fn check_disclosed_messages(
+            &self,
+            credential_type: CredentialType,
+            disclosed_messages: &[(usize, Vec<u8>)],
+        ) -> Result<(), anyhow::Error> {
+            self.selection()
+                .check_disclosed_messages(credential_type, disclosed_messages)
+        }
+    }
+
+    /// The index of a BBS message to disclose, and the value it must have, if any.
+    pub type SelectedMessage = (usize, Option<Vec<u8>>);
+
+    /// A selection of attributes to disclose from a credential. Attributes are named as in
+    /// [`CredentialType::attributes`], which maps them to the indices of their BBS messages.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct AttributeSelection {
+        /// Types of credential the proof may be made from, in order of preference.
+        pub credential_types: Vec<CredentialType>,
+
+        /// Attributes to disclose, whatever their values.
+        #[serde(default)]
+        pub disclose: Vec<String>,
+
+        /// Attributes to disclose that must have particular values, written as
+        /// [`AttributeEncoding::decode`] writes them (e.g. `age_over_21: "true"`).
+        #[serde(default)]
+        pub require: BTreeMap<String, String>,
+
+        /// Issuers, named as in the headers of their signatures, whose credentials the proof may be
+        /// made from. If empty, any issuer the verifier trusts will do.
+        #[serde(default)]
+        pub issuers: Vec<String>,
+    }
+
+    impl AttributeSelection {
+        /// Names of the attributes to disclose.
+        pub fn attributes(&self) -> impl Iterator<Item = &str> {
+            self.disclose
+                .iter()
+                .chain(self.require.keys())
+                .map(String::as_str)
+        }
+
+        /// Whether a proof may be made from a credential of the type, signed with the header.
+        pub fn accepts(&self, credential_type: CredentialType, header: &[u8]) -> bool {
+            self.credential_types.contains(&credential_type)
+                && (self.issuers.is_empty()
+                    || self
+                        .issuers
+                        .iter()
+                        .any(|issuer| issuer.as_bytes() == header))
+        }
+
+        /// Indices of the BBS messages to disclose from a credential of the type, each with the
+        /// value it must have, if any. Fails if that type of credential lacks any of the
+        /// attributes, if any is named twice, or if it selects the credential's type, which every
+        /// proof discloses anyway.
+        pub fn messages(
+            &self,
+            credential_type: CredentialType,
+        ) -> Result<Vec<SelectedMessage>, Error> {
+            let mut names = BTreeSet::new();
+            for name in self.attributes() {
+                if !names.insert(name) {
+                    return Err(Error::BadRequest {
+                        message: format!("{name} is selected more than once"),
+                    });
+                }
+            }
+            let index = |name: &str| {
+                let index =
+                    credential_type
+                        .attribute_index(name)
+                        .ok_or_else(|| Error::BadRequest {
+                            message: format!("{credential_type:?} has no attribute {name}"),
+                        })?;
+                if index == CredentialType::MESSAGE_INDEX {
+                    return Err(Error::BadRequest {
+                        message: format!("{name} is disclosed by every proof"),
+                    });
+                }
+                Ok(index)
+            };
+
+            let mut messages = Vec::new();
+            for name in &self.disclose {
+                messages.push((index(name)?, None));
+            }
+            for (name, value) in &self.require {
+                let index = index(name)?;
+                let (_, encoding) = credential_type.attributes()[index];
+                let message = encoding.encode(value).ok_or_else(|| Error::BadRequest {
+                    message: format!("{value:?} is not a valid value of {name}"),
+                })?;
+                messages.push((index, Some(message)));
+            }
+            messages.sort();
+
+            Ok(messages)
+        }
+
+        /// Check that the messages disclosed in a proof made from a credential of the type are
+        /// those this selection discloses.
+        fn check_disclosed_messages(
+            &self,
+            credential_type: CredentialType,
+            disclosed_messages: &[(usize, Vec<u8>)],
+        ) -> Result<(), anyhow::Error> {
+            let expected_messages = self.messages(credential_type)?;
+
+            let as_expected = disclosed_messages.len() == expected_messages.len()
+                && expected_messages.iter().all(|(index, value)| {
+                    disclosed_messages.iter().any(|(disclosed_index, message)| {
+                        disclosed_index == index && value.as_ref().is_none_or(|v| v == message)
+                    })
+                });
+            if !as_expected {
+                return Err(anyhow!(
+                    "proof should disclose exactly {:?}",
+                    self.describe()
+                ));
+            }
+
+            Ok(())
+        }
+
+        /// A human readable description of what the selection discloses.
+        fn describe(&self) -> Vec<String> {
+            self.disclose
+                .iter()
+                .cloned()
+                .chain(
+                    self.require
+                        .iter()
+                        .map(|(name, value)| format!("{name} = {value}")),
+                )
+                .collect()
+        }
     }
 
     /// A proof of some message, corresponding to a ProofRequest.
@@ -52,6 +556,9 @@ pub mod person {
         /// The header from the BBS signature.
         pub header: Vec<u8>,
 
+        /// Identifies which of the issuer's keys made the BBS signature.
+        pub key_id: String,
+
         /// The BBS proof.
         pub proof: Vec<u8>,
 
@@ -59,6 +566,36 @@ pub mod person {
         pub disclosed_messages: Vec<(usize, Vec<u8>)>,
     }
 
+    impl Proof {
+        /// The type of credential the proof was made from, as disclosed in it. See
+        /// [`CredentialType::from_disclosed_messages`].
+        pub fn credential_type(&self) -> Result<CredentialType, anyhow::Error> {
+            CredentialType::from_disclosed_messages(&self.disclosed_messages)
+        }
+    }
+
+    /// A person's decision whether to consent to disclosing attributes to a verifier.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct ConsentRecord {
+        /// The verifier that requested the proof.
+        pub verifier: String,
+
+        /// The type of proof requested.
+        pub proof_type: ProofType,
+
+        /// Names of the attributes the proof would disclose.
+        pub attributes: Vec<String>,
+
+        /// Whether the person was prompted, rather than their policy deciding for them.
+        pub prompted: bool,
+
+        /// Whether the person consented.
+        pub approved: bool,
+
+        /// When the person decided, in seconds since the UNIX epoch.
+        pub decided_at: u64,
+    }
+
     /// A request for a person to obtain a credential.
     #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
     pub struct ObtainCredentialRequest {
@@ -68,4 +605,536 @@ pub mod person {
         /// The issuer to obtain the credential from, as a DNS name that this actor can resolve.
         pub issuer: String,
     }
+
+    /// A request for a person to renew a credential, replacing it with one reflecting their current
+    /// details.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct ObtainRenewalRequest {
+        /// The credential type.
+        pub credential_type: CredentialType,
+
+        /// The issuer that issued the credential, as a DNS name that this actor can resolve.
+        pub issuer: String,
+
+        /// Serial number of the credential to renew.
+        pub serial_number: u64,
+    }
+}
+
+/// API objects for interacting with a relying party.
+pub mod relying_party {
+    use crate::{credential::CredentialType, messages::person::ProofType};
+    use serde::{Deserialize, Serialize};
+
+    /// A request for the relying party to grant a person access to whatever it guards.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct AccessRequest {
+        /// The person requesting access, as a DNS name that this actor can resolve.
+        pub person_hostname: String,
+    }
+
+    /// The relying party's decision on an access request.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct AccessDecision {
+        /// The person who requested access.
+        pub person_hostname: String,
+
+        /// Whether access was granted. Access is granted only if every required proof verified.
+        pub granted: bool,
+
+        /// When the decision was made, in seconds since the UNIX epoch.
+        pub decided_at: u64,
+
+        /// Outcome of each proof the relying party required.
+        pub proof_outcomes: Vec<ProofOutcome>,
+    }
+
+    /// The outcome of requesting and verifying a single proof.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct ProofOutcome {
+        /// The type of proof requested.
+        pub proof_type: ProofType,
+
+        /// The type of credential the proof was made from, if one was obtained.
+        pub credential_type: Option<CredentialType>,
+
+        /// The header from the BBS signature in the proof, if one was obtained.
+        pub header: Option<Vec<u8>>,
+
+        /// The BBS proof, if one was obtained.
+        pub proof: Option<Vec<u8>>,
+
+        /// Messages disclosed in the proof, if it verified. Tuple of message index and message.
+        pub disclosed_messages: Vec<(usize, Vec<u8>)>,
+
+        /// Description of why the proof could not be obtained or verified, if it wasn't.
+        pub failure: Option<String>,
+    }
+}
+
+/// API objects for interacting with a population of persons.
+pub mod population {
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    /// A request for every person in the population to request access from a relying party.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct PopulationAccessRequest {
+        /// The relying party, as a DNS name that this actor can resolve.
+        pub relying_party: String,
+    }
+
+    /// How many persons in a population managed to do something, and why the others didn't.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct PopulationStatistics {
+        /// Number of persons who tried.
+        pub attempts: usize,
+
+        /// Number of persons who succeeded.
+        pub successes: usize,
+
+        /// Number of failures, by reason. A reason is the code of the error a request failed with,
+        /// or the type of proof that an access request was denied for, serialized as JSON.
+        pub failures: BTreeMap<String, usize>,
+    }
+}
+
+/// API objects for interacting with a wallet.
+pub mod wallet {
+    use crate::{bbs::HolderPublicKey, credential::Credential};
+    use serde::{Deserialize, Serialize};
+
+    /// A request for the wallet to store a credential issued into it.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct StoreCredentialRequest {
+        /// The holder binding the credential is bound to, as [`HolderBinding::binding_id`] named it.
+        pub binding_id: String,
+
+        /// The credential.
+        pub credential: Credential,
+    }
+
+    /// A request for the wallet to replace a credential with its renewal.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct ReplaceCredentialRequest {
+        /// Serial number of the credential to replace. Only a credential of the same type from the
+        /// same issuer is replaced.
+        pub serial_number: u64,
+
+        /// The holder binding the renewal is bound to, as [`HolderBinding::binding_id`] named it.
+        pub binding_id: String,
+
+        /// The credential that replaces it.
+        pub credential: Credential,
+    }
+
+    /// A request for the wallet to generate a key to which an issuer can bind the credential it is
+    /// about to issue into the wallet.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct HolderBindingRequest {
+        /// The issuer, named as in the headers of its signatures. The wallet must trust it, and
+        /// only accepts a credential bound to the key from it.
+        pub issuer: String,
+    }
+
+    /// A key with which an issuer binds a credential to the wallet it issues it into. The wallet
+    /// keeps the secret key, so only it can prove anything from the credential.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct HolderBinding {
+        /// Identifies this binding. The issuer hands it back along with the credential bound to the
+        /// key.
+        pub binding_id: String,
+
+        /// The public key, to which the issuer binds the credential.
+        pub public_key: HolderPublicKey,
+
+        /// Proof that the wallet possesses the secret key, made for the issuer.
+        pub proof_of_possession: Vec<u8>,
+    }
+}
+
+/// API objects for interacting with a wallet maker.
+pub mod wallet_maker {
+    use crate::bbs::BbsPublicKey;
+    use serde::{Deserialize, Serialize};
+
+    /// A request for the wallet maker to attest that it made a wallet.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct AttestationRequest {
+        /// The hostname at which the wallet is reached.
+        pub wallet_hostname: String,
+    }
+
+    /// A wallet maker's public key, used to verify attestations it made.
+    #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct WalletMakerPublicKey {
+        /// The wallet maker's vendor name, as it appears in the header of its BBS signatures.
+        pub vendor: String,
+
+        /// The wallet maker's public key.
+        pub public_key: BbsPublicKey,
+    }
+}
+
+/// API objects for interacting with an auditor.
+pub mod auditor {
+    use crate::credential::CredentialType;
+    use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// An attribute of a person that some actor observed.
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct ObservedAttribute {
+        /// The type of credential in which the attribute was observed.
+        pub credential_type: CredentialType,
+
+        /// The name of the attribute, as in [`CredentialType::attributes`], or `issuer` for the
+        /// name of the issuer of the credential.
+        pub name: String,
+
+        /// The value of the attribute, or `None` if it could not be decoded.
+        pub value: Option<String>,
+    }
+
+    /// An auditor's report of what each actor in a deployment learned about each person.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+    pub struct AuditReport {
+        /// Attributes observed about each person, keyed by the person's hostname and then by the
+        /// hostname of the actor that observed them.
+        pub persons: BTreeMap<String, BTreeMap<String, BTreeSet<ObservedAttribute>>>,
+    }
+
+    impl AuditReport {
+        /// Record that the observer observed an attribute of the person.
+        pub fn observe(&mut self, person: &str, observer: &str, attribute: ObservedAttribute) {
+            self.persons
+                .entry(person.to_string())
+                .or_default()
+                .entry(observer.to_string())
+                .or_default()
+                .insert(attribute);
+        }
+
+        /// Names of the attributes of the person that the observer observed.
+        pub fn attribute_names(&self, person: &str, observer: &str) -> BTreeSet<&str> {
+            self.persons
+                .get(person)
+                .and_then(|observers| observers.get(observer))
+                .into_iter()
+                .flatten()
+                .map(|attribute| attribute.name.as_str())
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        issuer::{Delivery, IssuerPublicKey, LedgerEntry, LedgerQuery},
+        person::{AttributeSelection, CheckedAttributes, ProofRequest, ProofType},
+    };
+    use crate::{bbs::BbsKeypair, credential::CredentialType};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn check_disclosed_messages() {
+        let mut request = ProofRequest {
+            proof_type: ProofType::AgeOver { threshold: 21 },
+            verifier: "verifier".to_string(),
+            nonce: Vec::from([0]),
+            revocation_check: false,
+            validity_check: false,
+        };
+
+        let credential_type = (0, b"DriversLicense".to_vec());
+        let age_over_21 = [credential_type.clone(), (12, Vec::from([1]))];
+        let age_over_21_and_status = [
+            credential_type.clone(),
+            (12, Vec::from([1])),
+            (14, 12u64.to_be_bytes().to_vec()),
+        ];
+        let age_over_21_status_and_expiry = [
+            credential_type.clone(),
+            (12, Vec::from([1])),
+            (14, 12u64.to_be_bytes().to_vec()),
+            (16, 200u64.to_be_bytes().to_vec()),
+        ];
+        let age_over_21_status_and_validity = [
+            credential_type.clone(),
+            (12, Vec::from([1])),
+            (14, 12u64.to_be_bytes().to_vec()),
+            (15, 100u64.to_be_bytes().to_vec()),
+            (16, 200u64.to_be_bytes().to_vec()),
+        ];
+
+        assert_eq!(
+            request
+                .check_disclosed_messages(CredentialType::DriversLicense, &age_over_21)
+                .unwrap(),
+            CheckedAttributes::default()
+        );
+        request
+            .check_disclosed_messages(
+                CredentialType::DriversLicense,
+                &[credential_type.clone(), (12, Vec::from([0]))],
+            )
+            .unwrap_err();
+        // The proof must disclose the type of credential it was made from, and it must be the
+        // type the proof is checked against.
+        request
+            .check_disclosed_messages(CredentialType::DriversLicense, &age_over_21[1..])
+            .unwrap_err();
+        request
+            .check_disclosed_messages(CredentialType::LibraryCard, &age_over_21)
+            .unwrap_err();
+        request
+            .check_disclosed_messages(CredentialType::DriversLicense, &age_over_21_and_status)
+            .unwrap_err();
+
+        request.revocation_check = true;
+        assert_eq!(
+            request
+                .check_disclosed_messages(CredentialType::DriversLicense, &age_over_21_and_status)
+                .unwrap(),
+            CheckedAttributes {
+                status_list_index: Some(12),
+                valid_until: None,
+            }
+        );
+        request
+            .check_disclosed_messages(CredentialType::DriversLicense, &age_over_21)
+            .unwrap_err();
+        request
+            .check_disclosed_messages(
+                CredentialType::DriversLicense,
+                &age_over_21_status_and_expiry,
+            )
+            .unwrap_err();
+
+        request.validity_check = true;
+        assert_eq!(
+            request
+                .check_disclosed_messages(
+                    CredentialType::DriversLicense,
+                    &age_over_21_status_and_expiry
+                )
+                .unwrap(),
+            CheckedAttributes {
+                status_list_index: Some(12),
+                valid_until: Some(200),
+            }
+        );
+        // The validity check only needs when the credential expires, and disclosing when it became
+        // valid as well would narrow down when it was issued.
+        request
+            .check_disclosed_messages(
+                CredentialType::DriversLicense,
+                &age_over_21_status_and_validity,
+            )
+            .unwrap_err();
+        request
+            .check_disclosed_messages(CredentialType::DriversLicense, &age_over_21_and_status)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn attribute_selection() {
+        let selection = AttributeSelection {
+            credential_types: Vec::from([
+                CredentialType::DriversLicense,
+                CredentialType::LibraryCard,
+            ]),
+            disclose: Vec::from(["holder_name".to_string()]),
+            require: BTreeMap::from([("organ_donor".to_string(), "true".to_string())]),
+            issuers: Vec::from(["issuer/dmv-1".to_string()]),
+        };
+
+        assert_eq!(
+            selection.messages(CredentialType::DriversLicense).unwrap(),
+            [(2, None), (9, Some(Vec::from([1])))]
+        );
+        // Library cards don't record whether the holder is an organ donor.
+        selection.messages(CredentialType::LibraryCard).unwrap_err();
+
+        assert!(selection.accepts(CredentialType::DriversLicense, b"issuer/dmv-1"));
+        assert!(!selection.accepts(CredentialType::DriversLicense, b"issuer/dmv-2"));
+
+        let proof_type = ProofType::Attributes(selection);
+        proof_type
+            .check_disclosed_messages(
+                CredentialType::DriversLicense,
+                &[(2, b"Homer Simpson".to_vec()), (9, Vec::from([1]))],
+            )
+            .unwrap();
+        proof_type
+            .check_disclosed_messages(
+                CredentialType::DriversLicense,
+                &[(2, b"Homer Simpson".to_vec()), (9, Vec::from([0]))],
+            )
+            .unwrap_err();
+        proof_type
+            .check_disclosed_messages(CredentialType::DriversLicense, &[(2, b"Homer".to_vec())])
+            .unwrap_err();
+
+        // The shorthands are selections too.
+        for credential_type in [CredentialType::DriversLicense, CredentialType::LibraryCard] {
+            assert_eq!(
+                ProofType::HolderName
+                    .selection()
+                    .messages(credential_type)
+                    .unwrap(),
+                [(2, None)]
+            );
+        }
+        assert_eq!(
+            ProofType::AgeOver { threshold: 21 }
+                .selection()
+                .messages(CredentialType::DriversLicense)
+                .unwrap(),
+            [(12, Some(Vec::from([1])))]
+        );
+        ProofType::AgeOver { threshold: 19 }
+            .selection()
+            .messages(CredentialType::DriversLicense)
+            .unwrap_err();
+        assert_eq!(
+            ProofType::ResidesIn {
+                country: "US".to_string(),
+                region: Some("OH".to_string()),
+            }
+            .selection()
+            .messages(CredentialType::DriversLicense)
+            .unwrap(),
+            [(6, Some(b"OH".to_vec())), (8, Some(b"US".to_vec()))]
+        );
+    }
+
+    #[test]
+    fn attribute_selection_rejects_reserved_attributes() {
+        let selection = |name: &str| AttributeSelection {
+            credential_types: Vec::from([CredentialType::DriversLicense]),
+            disclose: Vec::from([name.to_string()]),
+            ..Default::default()
+        };
+
+        // Every proof discloses the credential's type, so selecting it too is a mistake.
+        selection("credential_type")
+            .messages(CredentialType::DriversLicense)
+            .unwrap_err();
+
+        // The checked attributes may be selected, but not when a check discloses them anyway.
+        let mut request = ProofRequest {
+            proof_type: ProofType::Attributes(selection("status_list_index")),
+            verifier: "verifier".to_string(),
+            nonce: Vec::from([0]),
+            revocation_check: false,
+            validity_check: false,
+        };
+        request.selection().unwrap();
+        request.revocation_check = true;
+        request.selection().unwrap_err();
+        request
+            .check_disclosed_messages(
+                CredentialType::DriversLicense,
+                &[
+                    (0, b"DriversLicense".to_vec()),
+                    (14, 12u64.to_be_bytes().to_vec()),
+                ],
+            )
+            .unwrap_err();
+
+        let mut request = ProofRequest {
+            proof_type: ProofType::Attributes(selection("valid_until")),
+            ..request
+        };
+        request.selection().unwrap();
+        request.validity_check = true;
+        request.selection().unwrap_err();
+    }
+
+    #[test]
+    fn attribute_selection_rejects_duplicate_attributes() {
+        let selection = AttributeSelection {
+            credential_types: Vec::from([CredentialType::DriversLicense]),
+            disclose: Vec::from(["holder_name".to_string(), "holder_name".to_string()]),
+            ..Default::default()
+        };
+        selection
+            .messages(CredentialType::DriversLicense)
+            .unwrap_err();
+
+        // Disclosing an attribute must not drop a requirement on its value.
+        let selection = AttributeSelection {
+            credential_types: Vec::from([CredentialType::DriversLicense]),
+            disclose: Vec::from(["organ_donor".to_string()]),
+            require: BTreeMap::from([("organ_donor".to_string(), "true".to_string())]),
+            ..Default::default()
+        };
+        selection
+            .messages(CredentialType::DriversLicense)
+            .unwrap_err();
+        ProofType::Attributes(selection)
+            .check_disclosed_messages(CredentialType::DriversLicense, &[(9, Vec::from([0]))])
+            .unwrap_err();
+    }
+
+    #[test]
+    fn issuer_key_validity() {
+        let mut key = IssuerPublicKey {
+            issuer: "issuer/dmv-1".to_string(),
+            key_id: "key-1".to_string(),
+            public_key: BbsKeypair::new("issuer/dmv-1").unwrap().public_key(),
+            not_before: 100,
+            not_after: None,
+        };
+        assert!(!key.valid_at(99));
+        assert!(key.valid_at(100));
+        assert!(key.valid_at(u64::MAX));
+
+        key.not_after = Some(200);
+        assert!(key.valid_at(199));
+        assert!(!key.valid_at(200));
+    }
+
+    #[test]
+    fn ledger_query() {
+        let entry = LedgerEntry {
+            credential_type: CredentialType::LibraryCard,
+            serial_number: 3,
+            status_list_index: 12,
+            encoded_credential: "{}".to_string(),
+            wallet_hostname: "wallet".to_string(),
+            issued_at: 1753729603,
+            revoked_at: None,
+            supersedes: None,
+            superseded_by: None,
+            delivery: Delivery::Delivered,
+        };
+
+        assert!(LedgerQuery::default().matches(&entry));
+        assert!(
+            LedgerQuery {
+                credential_type: Some(CredentialType::LibraryCard),
+                serial_number: Some(3),
+                wallet_hostname: Some("wallet".to_string()),
+            }
+            .matches(&entry)
+        );
+        assert!(
+            !LedgerQuery {
+                serial_number: Some(4),
+                ..Default::default()
+            }
+            .matches(&entry)
+        );
+        assert!(
+            !LedgerQuery {
+                credential_type: Some(CredentialType::DriversLicense),
+                wallet_hostname: Some("wallet".to_string()),
+                ..Default::default()
+            }
+            .matches(&entry)
+        );
+    }
 }
@@ -0,0 +1,222 @@
+//! Credential revocation using status lists published by issuers.
+//!
+//! Each credential carries a signed index into its issuer's status list, allocated at random when
+//! the credential is issued. Checking revocation requires the holder to disclose that index, but
+//! not the credential's serial number or any other attribute. Verifiers fetch the entire list, so
+//! the issuer doesn't learn which credential is being checked. The issuer signs when it published
+//! the list and when it will publish the next one, so that verifiers can refuse stale lists that
+//! might not show recent revocations.
+
+use crate::bbs::{BbsKeypair, BbsPublicKey};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+/// A bitstring recording which of an issuer's credentials are revoked.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusList {
+    /// Bit `i` (counting from the most significant bit of the first byte) is set if the credential
+    /// at status list index `i` is revoked.
+    revoked: Vec<u8>,
+}
+
+impl StatusList {
+    /// Construct a status list with room for at least `size` credentials, none of them revoked.
+    pub fn new(size: u64) -> Self {
+        Self {
+            revoked: vec![0; size.div_ceil(8) as usize],
+        }
+    }
+
+    /// Number of credentials the status list can track.
+    pub fn len(&self) -> u64 {
+        self.revoked.len() as u64 * 8
+    }
+
+    /// Whether the status list can track no credentials at all.
+    pub fn is_empty(&self) -> bool {
+        self.revoked.is_empty()
+    }
+
+    /// Make room for at least `size` credentials, none of the new ones revoked. Never shrinks the
+    /// list.
+    pub fn grow(&mut self, size: u64) {
+        let bytes = size.div_ceil(8) as usize;
+        if bytes > self.revoked.len() {
+            self.revoked.resize(bytes, 0);
+        }
+    }
+
+    /// Whether the credential at the index is revoked.
+    pub fn is_revoked(&self, index: u64) -> Result<bool, anyhow::Error> {
+        let (byte, mask) = self.position(index)?;
+        Ok(self.revoked[byte] & mask != 0)
+    }
+
+    /// Mark the credential at the index as revoked.
+    pub fn revoke(&mut self, index: u64) -> Result<(), anyhow::Error> {
+        let (byte, mask) = self.position(index)?;
+        self.revoked[byte] |= mask;
+        Ok(())
+    }
+
+    /// Sign the status list, as published at `issued_at` and to be updated by `next_update`, so
+    /// verifiers can check it was published by the issuer and is fresh.
+    pub fn sign(
+        &self,
+        issuer: &str,
+        key_id: &str,
+        keypair: &BbsKeypair,
+        issued_at: u64,
+        next_update: u64,
+    ) -> Result<SignedStatusList, anyhow::Error> {
+        Ok(SignedStatusList {
+            issuer: issuer.to_string(),
+            key_id: key_id.to_string(),
+            signature: keypair.sign(
+                issuer.as_bytes().to_vec(),
+                self.bbs_messages(issued_at, next_update),
+            )?,
+            status_list: self.clone(),
+            issued_at,
+            next_update,
+        })
+    }
+
+    fn bbs_messages(&self, issued_at: u64, next_update: u64) -> Vec<Vec<u8>> {
+        Vec::from([
+            self.revoked.clone(),
+            issued_at.to_be_bytes().to_vec(),
+            next_update.to_be_bytes().to_vec(),
+        ])
+    }
+
+    fn position(&self, index: u64) -> Result<(usize, u8), anyhow::Error> {
+        if index >= self.len() {
+            return Err(anyhow!("status list index {index} out of range"));
+        }
+        Ok(((index / 8) as usize, 0x80 >> (index % 8)))
+    }
+}
+
+/// A status list signed by the issuer that published it.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignedStatusList {
+    /// The issuer's name, as it appears in the header of its BBS signatures.
+    pub issuer: String,
+
+    /// Identifies which of the issuer's keys signed the status list.
+    pub key_id: String,
+
+    /// The status list.
+    pub status_list: StatusList,
+
+    /// When the issuer published the status list, in seconds since the UNIX epoch.
+    pub issued_at: u64,
+
+    /// When the issuer will publish the next status list, in seconds since the UNIX epoch. The
+    /// status list is stale from then on.
+    pub next_update: u64,
+
+    /// BBS signature over the status list and the times, with the issuer's name as the header.
+    pub signature: Vec<u8>,
+}
+
+impl SignedStatusList {
+    /// Verify the signature over the status list using the issuer's public key and return the
+    /// list.
+    pub fn verify(&self, public_key: &BbsPublicKey) -> Result<&StatusList, anyhow::Error> {
+        public_key.verify(
+            self.issuer.as_bytes().to_vec(),
+            self.status_list
+                .bbs_messages(self.issued_at, self.next_update),
+            self.signature.clone(),
+        )?;
+        Ok(&self.status_list)
+    }
+
+    /// Check that the status list is fresh at the time, in seconds since the UNIX epoch: that it
+    /// was published no more than `max_age` seconds earlier, and that the issuer hasn't yet
+    /// published the next one.
+    pub fn check_freshness(&self, time: u64, max_age: u64) -> Result<(), anyhow::Error> {
+        if time.saturating_sub(self.issued_at) > max_age || time >= self.next_update {
+            return Err(anyhow!(
+                "status list of {} published at {} is stale",
+                self.issuer,
+                self.issued_at
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatusList;
+    use crate::bbs::BbsKeypair;
+
+    #[test]
+    fn revoke() {
+        let mut status_list = StatusList::new(10);
+        assert_eq!(status_list.len(), 16);
+
+        status_list.revoke(9).unwrap();
+        for index in 0..16 {
+            assert_eq!(status_list.is_revoked(index).unwrap(), index == 9);
+        }
+
+        status_list.is_revoked(16).unwrap_err();
+        status_list.revoke(16).unwrap_err();
+
+        // Growing the list keeps revocations, and never shrinks it.
+        status_list.grow(20);
+        assert_eq!(status_list.len(), 24);
+        assert!(status_list.is_revoked(9).unwrap());
+        assert!(!status_list.is_revoked(16).unwrap());
+        status_list.grow(8);
+        assert_eq!(status_list.len(), 24);
+    }
+
+    #[test]
+    fn signed_status_list() {
+        let keypair = BbsKeypair::generate().unwrap();
+        let mut status_list = StatusList::new(64);
+        status_list.revoke(3).unwrap();
+
+        let signed = status_list
+            .sign("issuer/test", "key-1", &keypair, 1000, 1600)
+            .unwrap();
+        assert_eq!(signed.verify(&keypair.public_key()).unwrap(), &status_list);
+
+        // A tampered status list doesn't verify.
+        let mut tampered = signed.clone();
+        tampered.status_list.revoke(4).unwrap();
+        tampered.verify(&keypair.public_key()).unwrap_err();
+
+        // Nor does one passed off as published later.
+        let mut tampered = signed.clone();
+        tampered.issued_at = 2000;
+        tampered.next_update = 2600;
+        tampered.verify(&keypair.public_key()).unwrap_err();
+
+        // Nor does one verified with another issuer's key.
+        signed
+            .verify(&BbsKeypair::generate().unwrap().public_key())
+            .unwrap_err();
+    }
+
+    #[test]
+    fn status_list_freshness() {
+        let keypair = BbsKeypair::generate().unwrap();
+        let signed = StatusList::new(64)
+            .sign("issuer/test", "key-1", &keypair, 1000, 1600)
+            .unwrap();
+
+        signed.check_freshness(1000, 300).unwrap();
+        signed.check_freshness(1300, 300).unwrap();
+        // Too old for the verifier, though the issuer hasn't published the next one yet.
+        signed.check_freshness(1301, 300).unwrap_err();
+        // Superseded by the next one.
+        signed.check_freshness(1599, 3600).unwrap();
+        signed.check_freshness(1600, 3600).unwrap_err();
+    }
+}
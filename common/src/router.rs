@@ -2,8 +2,7 @@
 
 use anyhow::Context;
 use axum::{
-    Router,
-    http::StatusCode,
+    Json, Router,
     response::{IntoResponse, Response},
 };
 use clap::Parser;
@@ -11,7 +10,10 @@ use reqwest::ClientBuilder;
 use std::net::{Ipv4Addr, SocketAddr};
 use tokio::signal::unix::{SignalKind, signal};
 
-use crate::config::{Cli, Configuration};
+use crate::{
+    config::{Cli, Configuration},
+    error::Error,
+};
 
 static USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
@@ -42,12 +44,9 @@ pub async fn actor_main<
             config.common_configuration().listen_address
         ))?;
 
-    // TODO: further configure default client
-    let client = ClientBuilder::new().user_agent(USER_AGENT);
-
     // TODO: instantiate the axum::Router here so we can plug in appropriate middleware, but for now
     // it's easier to let each actor's main() do it
-    let (actor_name, routes) = callback(config, client)?;
+    let (actor_name, routes) = callback(config, client_builder())?;
 
     tracing::info!("started the {actor_name} simulator");
 
@@ -58,6 +57,12 @@ pub async fn actor_main<
     Ok(())
 }
 
+/// The default configuration of actors' HTTP clients.
+pub fn client_builder() -> ClientBuilder {
+    // TODO: further configure default client
+    ClientBuilder::new().user_agent(USER_AGENT)
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
@@ -79,15 +84,29 @@ async fn shutdown_signal() {
 }
 
 /// AppError wraps `anyhow::Error` with an implementation that renders errors into axum responses.
+/// If the error or any error in its chain of causes is an [`Error`], the response reports that
+/// error. Otherwise it reports [`Error::Internal`].
+#[derive(Debug)]
 pub struct AppError(anyhow::Error);
 
+impl AppError {
+    /// The typed error this error is reported as.
+    pub fn error(&self) -> Error {
+        self.0
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<Error>())
+            .cloned()
+            .unwrap_or_else(|| Error::Internal {
+                message: format!("{:#}", self.0),
+            })
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Something went wrong: {}", self.0),
-        )
-            .into_response()
+        tracing::debug!("request failed: {:#}", self.0);
+        let error = self.error();
+        (error.status(), Json(error)).into_response()
     }
 }
 
@@ -100,3 +119,34 @@ where
         Self(err.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AppError;
+    use crate::error::Error;
+    use anyhow::{Context, anyhow};
+
+    #[test]
+    fn app_error() {
+        let not_found = Error::NotFound {
+            message: "no credential with serial number 3".to_string(),
+        };
+
+        // Typed errors are found even when wrapped in context.
+        let error: AppError = Err::<(), _>(not_found.clone())
+            .context("failed to revoke credential")
+            .unwrap_err()
+            .into();
+        assert_eq!(error.error(), not_found);
+
+        let error: AppError = anyhow!("status list is full")
+            .context("failed to allocate status list index")
+            .into();
+        assert_eq!(
+            error.error(),
+            Error::Internal {
+                message: "failed to allocate status list index: status list is full".to_string()
+            }
+        );
+    }
+}
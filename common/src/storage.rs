@@ -0,0 +1,155 @@
+//! Persistent storage for actor state.
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    fmt::Debug,
+    fs::{self, File},
+    io::{BufReader, ErrorKind, Write},
+    marker::PhantomData,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+/// Where an actor persists some piece of state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StorageConfiguration {
+    /// State is kept in memory and lost when the actor exits.
+    #[default]
+    Memory,
+    /// State is stored as JSON in a file, which is replaced atomically on every write.
+    JsonFile {
+        /// Path to the file.
+        path: PathBuf,
+    },
+}
+
+impl StorageConfiguration {
+    /// Open the configured store.
+    pub fn open<T>(&self) -> Box<dyn Store<T>>
+    where
+        T: Serialize + DeserializeOwned + Clone + Debug + Send + 'static,
+    {
+        match self {
+            Self::Memory => Box::new(MemoryStore::default()),
+            Self::JsonFile { path } => Box::new(JsonFileStore::new(path.clone())),
+        }
+    }
+}
+
+/// A backend for persisting a value.
+pub trait Store<T>: Debug + Send + Sync {
+    /// Load the value, or `None` if none was ever stored.
+    fn load(&self) -> Result<Option<T>, anyhow::Error>;
+
+    /// Store the value, replacing any previously stored value.
+    fn store(&self, value: &T) -> Result<(), anyhow::Error>;
+}
+
+/// Keeps the value in memory.
+#[derive(Debug)]
+pub struct MemoryStore<T>(Mutex<Option<T>>);
+
+impl<T> Default for MemoryStore<T> {
+    fn default() -> Self {
+        Self(Mutex::new(None))
+    }
+}
+
+impl<T: Clone + Debug + Send> Store<T> for MemoryStore<T> {
+    fn load(&self) -> Result<Option<T>, anyhow::Error> {
+        Ok(self.0.lock().unwrap().clone())
+    }
+
+    fn store(&self, value: &T) -> Result<(), anyhow::Error> {
+        *self.0.lock().unwrap() = Some(value.clone());
+        Ok(())
+    }
+}
+
+/// Stores the value as JSON in a file.
+#[derive(Debug)]
+pub struct JsonFileStore<T> {
+    path: PathBuf,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> JsonFileStore<T> {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Debug> Store<T> for JsonFileStore<T> {
+    fn load(&self) -> Result<Option<T>, anyhow::Error> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error).context(format!("failed to open {}", self.path.display()));
+            }
+        };
+
+        serde_json::from_reader(BufReader::new(file))
+            .map(Some)
+            .context(format!("failed to parse {}", self.path.display()))
+    }
+
+    fn store(&self, value: &T) -> Result<(), anyhow::Error> {
+        // Write to a temporary file alongside the real one and then rename it over the real one, so
+        // that a crash never leaves a partially written file behind.
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        let temp_path = PathBuf::from(temp_path);
+
+        let mut temp_file = File::create(&temp_path)
+            .context(format!("failed to create {}", temp_path.display()))?;
+        serde_json::to_writer(&mut temp_file, value).context("failed to serialize value")?;
+        temp_file
+            .flush()
+            .and_then(|()| temp_file.sync_all())
+            .context(format!("failed to write {}", temp_path.display()))?;
+
+        fs::rename(&temp_path, &self.path).context(format!(
+            "failed to rename {} to {}",
+            temp_path.display(),
+            self.path.display()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StorageConfiguration;
+    use std::{env, fs, process};
+
+    #[test]
+    fn memory_store() {
+        let store = StorageConfiguration::Memory.open::<Vec<u64>>();
+
+        assert_eq!(store.load().unwrap(), None);
+        store.store(&Vec::from([1, 2])).unwrap();
+        assert_eq!(store.load().unwrap(), Some(Vec::from([1, 2])));
+    }
+
+    #[test]
+    fn json_file_store() {
+        let path = env::temp_dir().join(format!("didemo-json-file-store-{}", process::id()));
+        let config = StorageConfiguration::JsonFile { path: path.clone() };
+
+        let store = config.open::<Vec<u64>>();
+        assert_eq!(store.load().unwrap(), None);
+        store.store(&Vec::from([1, 2])).unwrap();
+        store.store(&Vec::from([1, 2, 3])).unwrap();
+
+        // A store opened later, as after a restart, sees the latest value.
+        let reopened_store = config.open::<Vec<u64>>();
+        assert_eq!(reopened_store.load().unwrap(), Some(Vec::from([1, 2, 3])));
+
+        fs::remove_file(path).unwrap();
+    }
+}
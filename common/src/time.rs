@@ -0,0 +1,12 @@
+//! Keeping time.
+
+use anyhow::Context;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The current time, in seconds since the UNIX epoch.
+pub fn now() -> Result<u64, anyhow::Error> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("system clock is before the UNIX epoch")?
+        .as_secs())
+}
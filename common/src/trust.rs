@@ -0,0 +1,387 @@
+//! A registry of trusted issuers, consulted by wallets and verifiers to resolve issuer public keys,
+//! and of the jurisdictions trusted to accredit them. Issuers consult it for the vendors whose
+//! wallets they trust.
+
+use crate::{
+    accreditation::{SignedAccreditations, accredits},
+    attestation::WalletAttestation,
+    bbs::BbsPublicKey,
+    credential::CredentialType,
+    error::{Error, send},
+    messages::{
+        issuer::IssuerPublicKey, jurisdiction::JurisdictionPublicKey,
+        wallet_maker::WalletMakerPublicKey,
+    },
+    revocation::{SignedStatusList, StatusList},
+    time::now,
+};
+use anyhow::{Context, anyhow};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// An issuer trusted by some actor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustedIssuer {
+    /// The issuer's name, as it appears in the header of its BBS signatures.
+    pub name: String,
+
+    /// The issuer's public key is fetched from this host, as a DNS name that the actor can
+    /// resolve.
+    pub hostname: String,
+}
+
+/// A jurisdiction trusted by some actor to accredit issuers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustedJurisdiction {
+    /// The jurisdiction's name, as it appears in the header of its BBS signatures.
+    pub name: String,
+
+    /// The jurisdiction's public key and accreditations are fetched from this host, as a DNS name
+    /// that the actor can resolve.
+    pub hostname: String,
+}
+
+/// A wallet vendor trusted by some actor to make wallets that protect credentials.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TrustedVendor {
+    /// The vendor's name, as it appears in the header of its wallet maker's BBS signatures.
+    pub name: String,
+
+    /// The public key of the vendor's wallet maker is fetched from this host, as a DNS name that
+    /// the actor can resolve.
+    pub hostname: String,
+}
+
+/// Resolves the public keys of trusted issuers, jurisdictions and vendors. Keys are pinned the first
+/// time they are fetched, so a key published later under the same name, or for issuers, which rotate
+/// their keys, under the same key ID, is never used.
+///
+/// Cloning the registry is cheap, and clones share pinned keys.
+#[derive(Debug, Clone)]
+pub struct TrustRegistry {
+    trusted_issuers: Vec<TrustedIssuer>,
+    trusted_jurisdictions: Vec<TrustedJurisdiction>,
+    trusted_vendors: Vec<TrustedVendor>,
+    http_client: Client,
+    /// Issuer keys, by issuer name and key ID.
+    pinned_keys: Arc<Mutex<HashMap<(String, String), BbsPublicKey>>>,
+    pinned_jurisdiction_keys: Arc<Mutex<HashMap<String, BbsPublicKey>>>,
+    pinned_vendor_keys: Arc<Mutex<HashMap<String, BbsPublicKey>>>,
+    /// Status lists published longer ago than this, in seconds, are refused as stale.
+    max_status_list_age: u64,
+}
+
+/// How old a status list may be, in seconds, unless configured otherwise.
+pub const DEFAULT_MAX_STATUS_LIST_AGE: u64 = 3600;
+
+impl TrustRegistry {
+    pub fn new(trusted_issuers: Vec<TrustedIssuer>, http_client: Client) -> Self {
+        Self {
+            trusted_issuers,
+            trusted_jurisdictions: Vec::new(),
+            trusted_vendors: Vec::new(),
+            http_client,
+            pinned_keys: Arc::new(Mutex::new(HashMap::new())),
+            pinned_jurisdiction_keys: Arc::new(Mutex::new(HashMap::new())),
+            pinned_vendor_keys: Arc::new(Mutex::new(HashMap::new())),
+            max_status_list_age: DEFAULT_MAX_STATUS_LIST_AGE,
+        }
+    }
+
+    /// Require issuers to be accredited by one of the jurisdictions. If there are none, any trusted
+    /// issuer may issue any type of credential.
+    pub fn with_jurisdictions(mut self, trusted_jurisdictions: Vec<TrustedJurisdiction>) -> Self {
+        self.trusted_jurisdictions = trusted_jurisdictions;
+        self
+    }
+
+    /// Trust wallets made by the vendors.
+    pub fn with_vendors(mut self, trusted_vendors: Vec<TrustedVendor>) -> Self {
+        self.trusted_vendors = trusted_vendors;
+        self
+    }
+
+    /// Refuse status lists published longer ago than `max_status_list_age` seconds.
+    pub fn with_max_status_list_age(mut self, max_status_list_age: u64) -> Self {
+        self.max_status_list_age = max_status_list_age;
+        self
+    }
+
+    /// Resolve the public key, identified by the key ID, of the issuer named by a BBS signature
+    /// header. Fails if the issuer is not trusted or the key is retired.
+    ///
+    /// The issuer's keys are fetched every time, so that verifiers notice promptly when a key is
+    /// retired, but each key is pinned the first time it is seen, so a key published later under
+    /// the same ID is never used.
+    pub async fn resolve(
+        &self,
+        header: &[u8],
+        key_id: &str,
+    ) -> Result<BbsPublicKey, anyhow::Error> {
+        let issuer_name =
+            str::from_utf8(header).context("failed to convert BBS header to issuer name")?;
+        let trusted_issuer = self.trusted_issuer(issuer_name)?;
+
+        let issuer_public_keys: Vec<IssuerPublicKey> = send(
+            &trusted_issuer.hostname,
+            self.http_client
+                .get(format!("http://{}/public-keys", trusted_issuer.hostname)),
+        )
+        .await
+        .context("failed to fetch issuer public keys")?
+        .json()
+        .await
+        .context("failed to deserialize issuer public keys")?;
+
+        let issuer_public_key = issuer_public_keys
+            .into_iter()
+            .find(|issuer_public_key| issuer_public_key.key_id == key_id)
+            .ok_or_else(|| Error::InvalidSignature {
+                message: format!("issuer {issuer_name} has no key {key_id}"),
+            })?;
+
+        if issuer_public_key.issuer != issuer_name {
+            return Err(anyhow!(
+                "host {} published key for {} rather than {issuer_name}",
+                trusted_issuer.hostname,
+                issuer_public_key.issuer,
+            ));
+        }
+
+        if !issuer_public_key.valid_at(now()?) {
+            return Err(Error::InvalidSignature {
+                message: format!("key {key_id} of issuer {issuer_name} is not valid now"),
+            }
+            .into());
+        }
+
+        // If another request pinned the key while we were fetching, stick with that one.
+        let pinned_key = self
+            .pinned_keys
+            .lock()
+            .unwrap()
+            .entry((issuer_name.to_string(), key_id.to_string()))
+            .or_insert(issuer_public_key.public_key.clone())
+            .clone();
+        if pinned_key != issuer_public_key.public_key {
+            return Err(anyhow!(
+                "host {} replaced key {key_id} of {issuer_name}",
+                trusted_issuer.hostname,
+            ));
+        }
+
+        Ok(pinned_key)
+    }
+
+    /// Fetch the current status list of the issuer named by a BBS signature header, verifying that
+    /// the issuer signed it and that it is fresh. Fails if the issuer is not trusted.
+    pub async fn status_list(&self, header: &[u8]) -> Result<StatusList, anyhow::Error> {
+        let issuer_name =
+            str::from_utf8(header).context("failed to convert BBS header to issuer name")?;
+        let trusted_issuer = self.trusted_issuer(issuer_name)?;
+
+        let status_list_response = send(
+            &trusted_issuer.hostname,
+            self.http_client
+                .get(format!("http://{}/status-list", trusted_issuer.hostname)),
+        )
+        .await
+        .context("failed to fetch issuer status list")?;
+
+        let signed_status_list: SignedStatusList = status_list_response
+            .json()
+            .await
+            .context("failed to deserialize status list")?;
+
+        if signed_status_list.issuer != issuer_name {
+            return Err(anyhow!(
+                "host {} published status list for {} rather than {issuer_name}",
+                trusted_issuer.hostname,
+                signed_status_list.issuer,
+            ));
+        }
+
+        let public_key = self.resolve(header, &signed_status_list.key_id).await?;
+        signed_status_list.verify(&public_key)?;
+        signed_status_list.check_freshness(now()?, self.max_status_list_age)?;
+
+        Ok(signed_status_list.status_list)
+    }
+
+    /// Check that the issuer named by a BBS signature header is accredited to issue the type of
+    /// credential by some trusted jurisdiction, verifying that the jurisdiction signed its
+    /// accreditations. Succeeds if no jurisdictions are trusted.
+    pub async fn check_accreditation(
+        &self,
+        header: &[u8],
+        credential_type: CredentialType,
+    ) -> Result<(), anyhow::Error> {
+        if self.trusted_jurisdictions.is_empty() {
+            return Ok(());
+        }
+
+        let issuer_name =
+            str::from_utf8(header).context("failed to convert BBS header to issuer name")?;
+
+        for trusted_jurisdiction in &self.trusted_jurisdictions {
+            let public_key = self.resolve_jurisdiction(trusted_jurisdiction).await?;
+
+            let signed_accreditations: SignedAccreditations = send(
+                &trusted_jurisdiction.hostname,
+                self.http_client.get(format!(
+                    "http://{}/accreditations",
+                    trusted_jurisdiction.hostname
+                )),
+            )
+            .await
+            .context("failed to fetch jurisdiction accreditations")?
+            .json()
+            .await
+            .context("failed to deserialize accreditations")?;
+
+            if signed_accreditations.jurisdiction != trusted_jurisdiction.name {
+                return Err(anyhow!(
+                    "host {} published accreditations for {} rather than {}",
+                    trusted_jurisdiction.hostname,
+                    signed_accreditations.jurisdiction,
+                    trusted_jurisdiction.name,
+                ));
+            }
+
+            let accreditations = signed_accreditations.verify(&public_key)?;
+            if accredits(accreditations, issuer_name, credential_type) {
+                return Ok(());
+            }
+        }
+
+        Err(Error::UnaccreditedIssuer {
+            message: format!(
+                "issuer {issuer_name} is not accredited to issue {credential_type:?} by any \
+                 trusted jurisdiction"
+            ),
+        }
+        .into())
+    }
+
+    /// Resolve the public key of a trusted jurisdiction.
+    async fn resolve_jurisdiction(
+        &self,
+        trusted_jurisdiction: &TrustedJurisdiction,
+    ) -> Result<BbsPublicKey, anyhow::Error> {
+        if let Some(public_key) = self
+            .pinned_jurisdiction_keys
+            .lock()
+            .unwrap()
+            .get(&trusted_jurisdiction.name)
+        {
+            return Ok(public_key.clone());
+        }
+
+        let jurisdiction_public_key: JurisdictionPublicKey = send(
+            &trusted_jurisdiction.hostname,
+            self.http_client.get(format!(
+                "http://{}/public-key",
+                trusted_jurisdiction.hostname
+            )),
+        )
+        .await
+        .context("failed to fetch jurisdiction public key")?
+        .json()
+        .await
+        .context("failed to deserialize jurisdiction public key")?;
+
+        if jurisdiction_public_key.jurisdiction != trusted_jurisdiction.name {
+            return Err(anyhow!(
+                "host {} published key for {} rather than {}",
+                trusted_jurisdiction.hostname,
+                jurisdiction_public_key.jurisdiction,
+                trusted_jurisdiction.name,
+            ));
+        }
+
+        Ok(self
+            .pinned_jurisdiction_keys
+            .lock()
+            .unwrap()
+            .entry(trusted_jurisdiction.name.clone())
+            .or_insert(jurisdiction_public_key.public_key)
+            .clone())
+    }
+
+    /// Check that a wallet attestation was made by a trusted vendor, verifying the vendor's
+    /// signature.
+    pub async fn check_attestation(
+        &self,
+        attestation: &WalletAttestation,
+    ) -> Result<(), anyhow::Error> {
+        let trusted_vendor = self
+            .trusted_vendors
+            .iter()
+            .find(|trusted_vendor| trusted_vendor.name == attestation.vendor)
+            .ok_or_else(|| Error::UntrustedWallet {
+                message: format!("wallet vendor {} is not trusted", attestation.vendor),
+            })?;
+
+        let pinned_key = self
+            .pinned_vendor_keys
+            .lock()
+            .unwrap()
+            .get(&trusted_vendor.name)
+            .cloned();
+        let public_key = match pinned_key {
+            Some(public_key) => public_key,
+            None => {
+                let wallet_maker_public_key: WalletMakerPublicKey = send(
+                    &trusted_vendor.hostname,
+                    self.http_client
+                        .get(format!("http://{}/public-key", trusted_vendor.hostname)),
+                )
+                .await
+                .context("failed to fetch wallet maker public key")?
+                .json()
+                .await
+                .context("failed to deserialize wallet maker public key")?;
+
+                if wallet_maker_public_key.vendor != trusted_vendor.name {
+                    return Err(anyhow!(
+                        "host {} published key for {} rather than {}",
+                        trusted_vendor.hostname,
+                        wallet_maker_public_key.vendor,
+                        trusted_vendor.name,
+                    ));
+                }
+
+                self.pinned_vendor_keys
+                    .lock()
+                    .unwrap()
+                    .entry(trusted_vendor.name.clone())
+                    .or_insert(wallet_maker_public_key.public_key)
+                    .clone()
+            }
+        };
+
+        attestation
+            .verify(&public_key)
+            .map_err(|error| Error::UntrustedWallet {
+                message: format!("wallet attestation invalid: {error:#}"),
+            })?;
+
+        Ok(())
+    }
+
+    fn trusted_issuer(&self, issuer_name: &str) -> Result<&TrustedIssuer, anyhow::Error> {
+        self.trusted_issuers
+            .iter()
+            .find(|trusted_issuer| trusted_issuer.name == issuer_name)
+            .ok_or_else(|| {
+                Error::UntrustedIssuer {
+                    message: format!("issuer {issuer_name} is not trusted"),
+                }
+                .into()
+            })
+    }
+}
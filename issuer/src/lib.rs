@@ -1 +1,836 @@
+//! Simulation of an issuer in a digital identity deployment.
 
+use anyhow::{Context, anyhow};
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::{get, post, put},
+};
+use didemo_common::{
+    attestation::WalletAttestation,
+    bbs::{BbsKeypair, HolderPublicKey},
+    config::{CommonConfiguration, Configuration},
+    credential::{
+        Credential, CredentialSignature, CredentialType, DriversLicense, DriversLicenseRequest,
+        LibraryCard, LibraryCardRequest, NEVER_EXPIRES,
+    },
+    error::{Error, send},
+    messages::{
+        issuer::{
+            Delivery, IssueCredentialRequest, IssuerPublicKey, LedgerEntry, LedgerQuery,
+            RenewCredentialRequest, RetireKeyRequest, RevokeCredentialRequest, RotateKeyRequest,
+        },
+        wallet::{
+            HolderBinding, HolderBindingRequest, ReplaceCredentialRequest, StoreCredentialRequest,
+        },
+    },
+    revocation::{SignedStatusList, StatusList},
+    router::AppError,
+    storage::{StorageConfiguration, Store},
+    time::now,
+    trust::{TrustRegistry, TrustedVendor},
+};
+use reqwest::{Client, ClientBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+
+/// Configuration for an issuer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuerConfiguration {
+    #[serde(flatten)]
+    pub common: CommonConfiguration,
+
+    /// A label identifying the issuer.
+    pub label: String,
+
+    /// Credentials this issuer is allowed to issue
+    pub credential_types: Vec<CredentialType>,
+
+    /// Number of credentials the issuer's status list can track at first. The list doubles in size
+    /// whenever it fills up. If the status list in storage is smaller than this, it is grown to this
+    /// size when the issuer starts.
+    #[serde(default = "default_status_list_size")]
+    pub status_list_size: u64,
+
+    /// Where the issuer persists its serial numbers, status list and ledger.
+    #[serde(default)]
+    pub storage: StorageConfiguration,
+
+    /// Vendors whose wallets this issuer issues credentials into. If any are configured, wallets
+    /// must present an attestation from one of them. Otherwise, any wallet is accepted.
+    #[serde(default)]
+    pub accepted_vendors: Vec<TrustedVendor>,
+
+    /// File holding the private part of the issuer's signing key, as written by
+    /// [`BbsKeypair::write_to_file`]. If the file doesn't exist, the issuer generates a key and
+    /// writes it there, along with the public part in a file with `.pub` appended to the name. When
+    /// the issuer rotates its key, it replaces both files. The public parts of the keys it rotated
+    /// away from are kept in its storage, along with when each is valid, so credentials signed
+    /// with them still verify after a restart. If not configured, the issuer generates a new key
+    /// every time it starts.
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+
+    /// How long credentials of each type remain valid once issued, in seconds. Credentials of
+    /// types not listed never expire.
+    #[serde(default)]
+    pub validity_periods: HashMap<CredentialType, u64>,
+
+    /// Credentials become valid at the start of a period of this many seconds, counted from the
+    /// UNIX epoch, and expire at the end of one, rather than exactly when they are issued and when
+    /// their validity period runs out. All the credentials of a type that the issuer issues in one
+    /// period expire together, so disclosing when a credential expires only reveals which period
+    /// it was issued in. The coarser the periods, the more credentials share each expiry, and the
+    /// harder it is for verifiers to link proofs by it.
+    #[serde(default = "default_expiry_granularity")]
+    pub expiry_granularity: u64,
+
+    /// How long a published status list remains fresh, in seconds. Verifiers must fetch a new one
+    /// after that.
+    #[serde(default = "default_status_list_lifetime")]
+    pub status_list_lifetime: u64,
+}
+
+fn default_status_list_size() -> u64 {
+    1024
+}
+
+fn default_status_list_lifetime() -> u64 {
+    // One hour.
+    3600
+}
+
+fn default_expiry_granularity() -> u64 {
+    // Thirty days.
+    2592000
+}
+
+impl Configuration for IssuerConfiguration {
+    fn common_configuration(&self) -> &CommonConfiguration {
+        &self.common
+    }
+}
+
+/// Issuer state that must survive restarts, so that serial numbers and status list indices are
+/// never reused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IssuerState {
+    last_serial_number: u64,
+    status_list: StatusList,
+    /// Every credential the issuer has issued, in order of issuance.
+    ledger: Vec<LedgerEntry>,
+    /// Every key the issuer has signed with, including retired ones, in order of generation. The
+    /// last one is the key it currently signs with.
+    #[serde(default)]
+    keys: Vec<IssuerPublicKey>,
+}
+
+impl IssuerState {
+    /// Allocate an unused status list index, at random so that it reveals nothing about when the
+    /// credential was issued. If every index is allocated, the status list doubles in size first,
+    /// which reveals no more than the size of the list already did: roughly how many credentials
+    /// the issuer has issued.
+    fn allocate_status_list_index(&mut self) -> u64 {
+        if self.ledger.len() as u64 >= self.status_list.len() {
+            self.status_list.grow(self.status_list.len().max(8) * 2);
+        }
+
+        let allocated: HashSet<_> = self
+            .ledger
+            .iter()
+            .map(|entry| entry.status_list_index)
+            .collect();
+        loop {
+            let candidate = rand::random_range(0..self.status_list.len());
+            if !allocated.contains(&candidate) {
+                return candidate;
+            }
+        }
+    }
+
+    /// The ledger entry for the credential with the serial number.
+    fn entry_mut(&mut self, serial_number: u64) -> Result<&mut LedgerEntry, Error> {
+        self.ledger
+            .iter_mut()
+            .find(|entry| entry.serial_number == serial_number)
+            .ok_or_else(|| Error::NotFound {
+                message: format!("no credential with serial number {serial_number}"),
+            })
+    }
+
+    /// Revoke the credential with the serial number, if it isn't already.
+    fn revoke(&mut self, serial_number: u64) -> Result<(), anyhow::Error> {
+        let entry = self.entry_mut(serial_number)?;
+        if entry.revoked_at.is_none() {
+            entry.revoked_at = Some(now()?);
+        }
+        let status_list_index = entry.status_list_index;
+        self.status_list.revoke(status_list_index)
+    }
+}
+
+/// The public key of the keypair, as a key for the issuer valid from `not_before` until it is
+/// retired.
+fn issuer_public_key(issuer: &str, keypair: &BbsKeypair, not_before: u64) -> IssuerPublicKey {
+    IssuerPublicKey {
+        issuer: issuer.to_string(),
+        key_id: keypair.public_key().key_id(),
+        public_key: keypair.public_key(),
+        not_before,
+        not_after: None,
+    }
+}
+
+struct Issuer {
+    config: IssuerConfiguration,
+    actor_name: String,
+    http_client: Client,
+    trust_registry: TrustRegistry,
+    /// The key the issuer currently signs credentials and status lists with, the last of
+    /// `state.keys`.
+    signing_keypair: BbsKeypair,
+    state_store: Box<dyn Store<IssuerState>>,
+    state: IssuerState,
+}
+
+impl Issuer {
+    /// The public key the issuer currently signs with.
+    fn signing_key(&self) -> &IssuerPublicKey {
+        self.state.keys.last().expect("issuer always has a key")
+    }
+
+    /// When a credential issued at `issued_at` becomes valid, in seconds since the UNIX epoch: the
+    /// start of the expiry period it was issued in, so that it reveals no more than when the
+    /// credential expires does.
+    fn valid_from(&self, issued_at: u64) -> u64 {
+        let granularity = self.config.expiry_granularity.max(1);
+        issued_at - issued_at % granularity
+    }
+
+    /// When a credential of the type issued at `issued_at` expires, in seconds since the UNIX
+    /// epoch.
+    fn valid_until(&self, credential_type: CredentialType, issued_at: u64) -> u64 {
+        match self.config.validity_periods.get(&credential_type) {
+            Some(validity_period) => {
+                let granularity = self.config.expiry_granularity.max(1);
+                issued_at
+                    .saturating_add(*validity_period)
+                    .div_ceil(granularity)
+                    .saturating_mul(granularity)
+            }
+            None => NEVER_EXPIRES,
+        }
+    }
+
+    /// Check that the issuer may issue the requested credential, optionally in place of the one with
+    /// the serial number `supersedes`.
+    fn check_issuable(
+        &self,
+        request: &IssueCredentialRequest,
+        supersedes: Option<u64>,
+    ) -> Result<(), AppError> {
+        // This is where an issuer would perform actual verification that the person is a legitimate
+        // member of some group, citizen of some country, allowed to operate a motor vehicle or
+        // whatever. We do not simulate such verification and assume they succeed.
+
+        // TODO: other policy checks? Uniqueness of certain fields?
+
+        if !self
+            .config
+            .credential_types
+            .contains(&request.credential_type)
+        {
+            return Err(Error::NotPermitted {
+                message: format!(
+                    "not permitted to issue credential {:?}",
+                    request.credential_type
+                ),
+            }
+            .into());
+        }
+
+        if let Some(supersedes) = supersedes {
+            let old_entry = self
+                .state
+                .ledger
+                .iter()
+                .find(|entry| entry.serial_number == supersedes)
+                .ok_or_else(|| Error::NotFound {
+                    message: format!("no credential with serial number {supersedes}"),
+                })?;
+            if old_entry.credential_type != request.credential_type {
+                return Err(Error::BadRequest {
+                    message: format!(
+                        "credential {supersedes} is a {:?}, not a {:?}",
+                        old_entry.credential_type, request.credential_type
+                    ),
+                }
+                .into());
+            }
+            if old_entry.wallet_hostname != request.wallet_hostname {
+                return Err(Error::NotPermitted {
+                    message: format!(
+                        "credential {supersedes} was not issued into wallet {}",
+                        request.wallet_hostname
+                    ),
+                }
+                .into());
+            }
+            if old_entry.revoked_at.is_some() || old_entry.superseded_by.is_some() {
+                return Err(Error::NotPermitted {
+                    message: format!("credential {supersedes} was revoked or already renewed"),
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the issuer may issue the requested credential, optionally in place of the one with
+    /// the serial number `supersedes`, then sign it, binding it to the holder's key, and record it in
+    /// the ledger as pending delivery. The superseded credential is marked as such at the same time,
+    /// so that it can't be renewed twice. Returns the credential's serial number and the credential.
+    fn sign_credential(
+        &mut self,
+        request: &IssueCredentialRequest,
+        holder_public_key: &HolderPublicKey,
+        supersedes: Option<u64>,
+    ) -> Result<(u64, Credential), AppError> {
+        // The issuer wasn't locked while the wallet bound the credential to a holder key, so check
+        // again in case the superseded credential was revoked or renewed in the meantime.
+        self.check_issuable(request, supersedes)?;
+
+        // Record the credential before it leaves the issuer, so that its serial number and status list
+        // index are never reused, even if the wallet rejects it.
+        let mut state = self.state.clone();
+        let serial_number = state.last_serial_number + 1;
+        let status_list_index = state.allocate_status_list_index();
+        let issued_at = now()?;
+        let valid_from = self.valid_from(issued_at);
+        let valid_until = self.valid_until(request.credential_type, issued_at);
+
+        let (bbs_messages, encoded_credential) = match request.credential_type {
+            CredentialType::LibraryCard => {
+                let library_card_request: LibraryCardRequest =
+                    serde_json::from_str(&request.requested_credential).map_err(|error| {
+                        Error::BadRequest {
+                            message: format!("failed to deserialize library card request: {error}"),
+                        }
+                    })?;
+
+                let library_card = LibraryCard {
+                    library_name: self.config.label.clone(),
+                    holder_name: library_card_request.holder_name,
+                    serial_number,
+                    status_list_index,
+                    valid_from,
+                    valid_until,
+                };
+                let issued_credential = serde_json::to_string(&library_card)
+                    .context("failed to serialize library card")?;
+
+                (library_card.bbs_messages(), issued_credential)
+            }
+            CredentialType::DriversLicense => {
+                let drivers_license_request: DriversLicenseRequest =
+                    serde_json::from_str(&request.requested_credential).map_err(|error| {
+                        Error::BadRequest {
+                            message: format!(
+                                "failed to deserialize driver's license request: {error}"
+                            ),
+                        }
+                    })?;
+
+                let drivers_license = DriversLicense {
+                    issuing_jurisdiction: self.config.label.clone(),
+                    holder_name: drivers_license_request.holder_name,
+                    serial_number,
+                    home_address: drivers_license_request.home_address,
+                    organ_donor: drivers_license_request.organ_donor,
+                    birthdate: drivers_license_request.birthdate,
+                    age_over: DriversLicense::age_over(
+                        drivers_license_request.birthdate,
+                        issued_at,
+                    ),
+                    status_list_index,
+                    valid_from,
+                    valid_until,
+                };
+                let issued_credential = serde_json::to_string(&drivers_license)
+                    .context("failed to serialize driver's license")?;
+
+                (drivers_license.bbs_messages(), issued_credential)
+            }
+        };
+
+        let header = self.actor_name.as_bytes().to_vec();
+        let key_id = self.signing_key().key_id.clone();
+        let signature =
+            self.signing_keypair
+                .sign_bound(header.clone(), bbs_messages, holder_public_key)?;
+
+        state.last_serial_number = serial_number;
+        state.ledger.push(LedgerEntry {
+            credential_type: request.credential_type,
+            serial_number,
+            status_list_index,
+            encoded_credential: encoded_credential.clone(),
+            wallet_hostname: request.wallet_hostname.clone(),
+            issued_at,
+            revoked_at: None,
+            supersedes,
+            superseded_by: None,
+            delivery: Delivery::Pending,
+        });
+        if let Some(supersedes) = supersedes {
+            state.entry_mut(supersedes)?.superseded_by = Some(serial_number);
+        }
+        self.state_store
+            .store(&state)
+            .context("failed to store issuer state")?;
+        self.state = state;
+
+        let issued_credential = Credential {
+            credential_type: request.credential_type,
+            encoded_credential,
+            signature: CredentialSignature {
+                signature,
+                header,
+                key_id,
+            },
+        };
+
+        Ok((serial_number, issued_credential))
+    }
+
+    /// Record whether the credential with the serial number reached the wallet it was issued into.
+    /// A credential the wallet didn't accept is also revoked: nobody should hold it, but it was
+    /// signed and left the issuer. If it was a renewal, the credential it superseded is revoked once
+    /// the wallet holds the new one, or can be renewed again if the wallet didn't accept it.
+    fn record_delivery(
+        &mut self,
+        serial_number: u64,
+        delivered: bool,
+    ) -> Result<(), anyhow::Error> {
+        let mut state = self.state.clone();
+        let entry = state.entry_mut(serial_number)?;
+        let supersedes = entry.supersedes;
+        if delivered {
+            entry.delivery = Delivery::Delivered;
+            if let Some(supersedes) = supersedes {
+                state.revoke(supersedes)?;
+            }
+        } else {
+            entry.delivery = Delivery::Failed;
+            state.revoke(serial_number)?;
+            if let Some(supersedes) = supersedes {
+                state.entry_mut(supersedes)?.superseded_by = None;
+            }
+        }
+        self.state_store
+            .store(&state)
+            .context("failed to store issuer state")?;
+        self.state = state;
+
+        Ok(())
+    }
+}
+
+/// Construct an issuer from its configuration. Returns the actor's name and the routes it serves.
+pub fn actor(
+    config: IssuerConfiguration,
+    client_builder: ClientBuilder,
+) -> Result<(String, Router), anyhow::Error> {
+    let http_client = client_builder.build()?;
+
+    let actor_name = format!("issuer/{}", config.label);
+
+    let state_store: Box<dyn Store<IssuerState>> = config.storage.open();
+    let mut state = state_store
+        .load()
+        .context("failed to load issuer state")?
+        .unwrap_or_else(|| IssuerState {
+            last_serial_number: 0,
+            status_list: StatusList::new(config.status_list_size),
+            ledger: Vec::new(),
+            keys: Vec::new(),
+        });
+    state.status_list.grow(config.status_list_size);
+
+    let signing_keypair = match &config.key_file {
+        Some(key_file) => BbsKeypair::read_or_generate(key_file)?,
+        None => BbsKeypair::generate()?,
+    };
+    // A key the issuer hasn't signed with before, because it was just generated or because the
+    // issuer stopped after writing it to the key file but before storing its state, becomes the
+    // signing key. Keys signed with before remain valid until retired.
+    let key_id = signing_keypair.public_key().key_id();
+    match state.keys.iter().position(|key| key.key_id == key_id) {
+        None => {
+            state
+                .keys
+                .push(issuer_public_key(&actor_name, &signing_keypair, now()?));
+            state_store
+                .store(&state)
+                .context("failed to store issuer state")?;
+        }
+        Some(position) if position + 1 != state.keys.len() => {
+            return Err(anyhow!(
+                "key file holds key {key_id}, which the issuer has rotated away from"
+            ));
+        }
+        Some(_) => {}
+    }
+    tracing::info!(
+        last_serial_number = state.last_serial_number,
+        "loaded issuer state"
+    );
+
+    let issuer = Issuer {
+        trust_registry: TrustRegistry::new(Vec::new(), http_client.clone())
+            .with_vendors(config.accepted_vendors.clone()),
+        config,
+        actor_name: actor_name.clone(),
+        http_client,
+        signing_keypair,
+        state_store,
+        state,
+    };
+
+    let routes = Router::new()
+        .route("/config", get(serve_config))
+        .route("/issue", put(issue_credential))
+        .route("/renew", put(renew_credential))
+        .route("/public-key", get(serve_public_key))
+        .route("/public-keys", get(serve_public_keys))
+        .route("/rotate-key", post(rotate_key))
+        .route("/retire-key", post(retire_key))
+        .route("/status-list", get(serve_status_list))
+        .route("/revoke", post(revoke_credential))
+        .route("/ledger", get(serve_ledger))
+        .with_state(Arc::new(Mutex::new(issuer)));
+
+    Ok((actor_name, routes))
+}
+
+/// Issue the requested credential.
+#[axum::debug_handler]
+async fn issue_credential(
+    State(issuer): State<Arc<Mutex<Issuer>>>,
+    Json(request): Json<IssueCredentialRequest>,
+) -> Result<StatusCode, AppError> {
+    issue(&issuer, request, None).await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// Issue a credential in place of one previously issued, e.g. because the holder's details changed.
+/// The old credential is revoked once the wallet holding it has replaced it with the new one.
+#[axum::debug_handler]
+async fn renew_credential(
+    State(issuer): State<Arc<Mutex<Issuer>>>,
+    Json(request): Json<RenewCredentialRequest>,
+) -> Result<StatusCode, AppError> {
+    let serial_number = issue(&issuer, request.issue_request, Some(request.serial_number)).await?;
+
+    tracing::info!(
+        serial_number,
+        supersedes = request.serial_number,
+        "renewed credential"
+    );
+
+    Ok(StatusCode::CREATED)
+}
+
+/// Issue a credential into a wallet, optionally replacing a credential previously issued into it.
+/// Returns the new credential's serial number.
+async fn issue(
+    issuer: &Mutex<Issuer>,
+    request: IssueCredentialRequest,
+    supersedes: Option<u64>,
+) -> Result<u64, AppError> {
+    // Refuse requests the issuer can't grant before asking the wallet for anything. Check the wallet
+    // without holding the lock, since that means contacting the wallet.
+    let (actor_name, accepted_vendors, http_client, trust_registry) = {
+        let issuer = issuer.lock().await;
+        issuer.check_issuable(&request, supersedes)?;
+        (
+            issuer.actor_name.clone(),
+            issuer.config.accepted_vendors.clone(),
+            issuer.http_client.clone(),
+            issuer.trust_registry.clone(),
+        )
+    };
+    if !accepted_vendors.is_empty() {
+        check_wallet(&http_client, &trust_registry, &request.wallet_hostname).await?;
+    }
+
+    // The wallet generates a fresh key for this one credential and hands over only its public key,
+    // which commits to the secret key. We bind the credential to it without ever learning the
+    // secret key, after checking that the wallet does hold it.
+    let holder_binding: HolderBinding = send(
+        &request.wallet_hostname,
+        http_client
+            .post(format!(
+                "http://{}/holder-bindings",
+                request.wallet_hostname
+            ))
+            .json(&HolderBindingRequest {
+                issuer: actor_name.clone(),
+            }),
+    )
+    .await?
+    .json()
+    .await
+    .context("failed to deserialize holder binding")?;
+    holder_binding
+        .public_key
+        .verify_possession(actor_name.as_bytes(), holder_binding.proof_of_possession)?;
+
+    let (serial_number, issued_credential) =
+        issuer
+            .lock()
+            .await
+            .sign_credential(&request, &holder_binding.public_key, supersedes)?;
+
+    // The issuer isn't locked while contacting the wallet, which may call back into this issuer to
+    // resolve its public key.
+
+    let wallet_request = match supersedes {
+        Some(supersedes) => http_client
+            .post(format!(
+                "http://{}/replace-credential",
+                request.wallet_hostname
+            ))
+            .json(&ReplaceCredentialRequest {
+                serial_number: supersedes,
+                binding_id: holder_binding.binding_id,
+                credential: issued_credential,
+            }),
+        None => http_client
+            .put(format!("http://{}/credentials", request.wallet_hostname))
+            .json(&StoreCredentialRequest {
+                binding_id: holder_binding.binding_id,
+                credential: issued_credential,
+            }),
+    };
+    let delivery = send(&request.wallet_hostname, wallet_request).await;
+    issuer
+        .lock()
+        .await
+        .record_delivery(serial_number, delivery.is_ok())?;
+    delivery?;
+
+    tracing::info!(
+        credential_type = ?request.credential_type,
+        serial_number,
+        wallet_hostname = request.wallet_hostname,
+        "issued credential"
+    );
+
+    Ok(serial_number)
+}
+
+/// Check that the wallet at the hostname was made by an accepted vendor, by asking it for its
+/// wallet maker's attestation.
+async fn check_wallet(
+    http_client: &Client,
+    trust_registry: &TrustRegistry,
+    wallet_hostname: &str,
+) -> Result<(), anyhow::Error> {
+    let attestation: WalletAttestation = match send(
+        wallet_hostname,
+        http_client.get(format!("http://{wallet_hostname}/attestation")),
+    )
+    .await
+    {
+        Ok(response) => response
+            .json()
+            .await
+            .context("failed to deserialize wallet attestation")?,
+        Err(Error::Upstream {
+            error: Some(error), ..
+        }) if matches!(*error, Error::NotFound { .. }) => {
+            return Err(Error::UntrustedWallet {
+                message: format!("wallet {wallet_hostname} has no attestation"),
+            }
+            .into());
+        }
+        Err(error) => return Err(error.into()),
+    };
+
+    // An attestation for some other wallet proves nothing about this one.
+    if attestation.wallet_hostname != wallet_hostname {
+        return Err(Error::UntrustedWallet {
+            message: format!(
+                "wallet {wallet_hostname} presented attestation for {}",
+                attestation.wallet_hostname
+            ),
+        }
+        .into());
+    }
+
+    trust_registry.check_attestation(&attestation).await
+}
+
+/// Print the configuration.
+async fn serve_config(State(issuer): State<Arc<Mutex<Issuer>>>) -> Json<IssuerConfiguration> {
+    tracing::info!("serving config endpoint");
+    Json(issuer.lock().await.config.clone())
+}
+
+/// Publish the public key the issuer currently signs with.
+async fn serve_public_key(State(issuer): State<Arc<Mutex<Issuer>>>) -> Json<IssuerPublicKey> {
+    Json(issuer.lock().await.signing_key().clone())
+}
+
+/// Publish all of the issuer's public keys, including retired ones, so wallets and verifiers can
+/// check credentials signed with any of them and learn when each key is retired.
+async fn serve_public_keys(State(issuer): State<Arc<Mutex<Issuer>>>) -> Json<Vec<IssuerPublicKey>> {
+    Json(issuer.lock().await.state.keys.clone())
+}
+
+/// Start signing with a new key. Credentials signed with the previous key remain valid until it is
+/// retired, either after the requested period or explicitly.
+async fn rotate_key(
+    State(issuer): State<Arc<Mutex<Issuer>>>,
+    Json(request): Json<RotateKeyRequest>,
+) -> Result<Json<IssuerPublicKey>, AppError> {
+    let mut issuer = issuer.lock().await;
+
+    let rotated_at = now()?;
+    let not_after = request
+        .retire_after
+        .map(|retire_after| {
+            rotated_at
+                .checked_add(retire_after)
+                .ok_or_else(|| Error::BadRequest {
+                    message: format!("retire_after {retire_after} is too long"),
+                })
+        })
+        .transpose()?;
+
+    // Write the key file first: if the issuer stops before storing its state, it adopts the key
+    // when it restarts.
+    let keypair = BbsKeypair::generate()?;
+    if let Some(key_file) = &issuer.config.key_file {
+        keypair.write_to_key_files(key_file)?;
+    }
+    let public_key = issuer_public_key(&issuer.actor_name, &keypair, rotated_at);
+
+    let mut state = issuer.state.clone();
+    if let Some(not_after) = not_after {
+        let previous_key = state.keys.last_mut().expect("issuer always has a key");
+        previous_key.not_after = Some(
+            previous_key
+                .not_after
+                .map_or(not_after, |t| t.min(not_after)),
+        );
+    }
+    state.keys.push(public_key.clone());
+    issuer
+        .state_store
+        .store(&state)
+        .context("failed to store issuer state")?;
+    issuer.state = state;
+    issuer.signing_keypair = keypair;
+
+    tracing::info!(key_id = public_key.key_id, "rotated signing key");
+
+    Ok(Json(public_key))
+}
+
+/// Retire one of the issuer's keys immediately. The key the issuer currently signs with can't be
+/// retired without first rotating to a new one.
+async fn retire_key(
+    State(issuer): State<Arc<Mutex<Issuer>>>,
+    Json(request): Json<RetireKeyRequest>,
+) -> Result<StatusCode, AppError> {
+    let mut issuer = issuer.lock().await;
+
+    if issuer.signing_key().key_id == request.key_id {
+        return Err(Error::BadRequest {
+            message: format!("key {} is the signing key", request.key_id),
+        }
+        .into());
+    }
+
+    let mut state = issuer.state.clone();
+    let key = state
+        .keys
+        .iter_mut()
+        .find(|key| key.key_id == request.key_id)
+        .ok_or_else(|| Error::NotFound {
+            message: format!("no key {}", request.key_id),
+        })?;
+    let retired_at = now()?;
+    key.not_after = Some(key.not_after.map_or(retired_at, |t| t.min(retired_at)));
+    issuer
+        .state_store
+        .store(&state)
+        .context("failed to store issuer state")?;
+    issuer.state = state;
+
+    tracing::info!(key_id = request.key_id, "retired key");
+
+    Ok(StatusCode::OK)
+}
+
+/// Publish the issuer's status list, so verifiers can check whether credentials were revoked.
+async fn serve_status_list(
+    State(issuer): State<Arc<Mutex<Issuer>>>,
+) -> Result<Json<SignedStatusList>, AppError> {
+    let issuer = issuer.lock().await;
+    let issued_at = now()?;
+    Ok(Json(issuer.state.status_list.sign(
+        &issuer.actor_name,
+        &issuer.signing_key().key_id,
+        &issuer.signing_keypair,
+        issued_at,
+        issued_at.saturating_add(issuer.config.status_list_lifetime),
+    )?))
+}
+
+/// Revoke a previously issued credential.
+async fn revoke_credential(
+    State(issuer): State<Arc<Mutex<Issuer>>>,
+    Json(request): Json<RevokeCredentialRequest>,
+) -> Result<StatusCode, AppError> {
+    let mut issuer = issuer.lock().await;
+
+    let mut state = issuer.state.clone();
+    state.revoke(request.serial_number)?;
+
+    issuer
+        .state_store
+        .store(&state)
+        .context("failed to store issuer state")?;
+    issuer.state = state;
+
+    tracing::info!(serial_number = request.serial_number, "revoked credential");
+
+    Ok(StatusCode::OK)
+}
+
+/// List the credentials the issuer has issued that match the query.
+async fn serve_ledger(
+    State(issuer): State<Arc<Mutex<Issuer>>>,
+    Query(query): Query<LedgerQuery>,
+) -> Json<Vec<LedgerEntry>> {
+    let issuer = issuer.lock().await;
+    Json(
+        issuer
+            .state
+            .ledger
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .cloned()
+            .collect(),
+    )
+}
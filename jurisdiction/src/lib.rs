@@ -0,0 +1,104 @@
+//! Simulation of a jurisdiction in a digital identity deployment.
+
+use axum::{Json, Router, extract::State, routing::get};
+use didemo_common::{
+    accreditation::{self, Accreditation, SignedAccreditations},
+    bbs::BbsKeypair,
+    config::{CommonConfiguration, Configuration},
+    messages::jurisdiction::JurisdictionPublicKey,
+    router::AppError,
+};
+use reqwest::ClientBuilder;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+
+/// Configuration for a jurisdiction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JurisdictionConfiguration {
+    #[serde(flatten)]
+    pub common: CommonConfiguration,
+
+    /// A label identifying the jurisdiction.
+    pub label: String,
+
+    /// Issuers the jurisdiction accredits, and the types of credentials it accredits them to issue.
+    pub accreditations: Vec<Accreditation>,
+
+    /// File holding the private part of the jurisdiction's signing key, as written by
+    /// [`BbsKeypair::write_to_key_files`]. If the file doesn't exist, the jurisdiction generates a
+    /// key and writes it there, along with the public part in a file with `.pub` appended to the
+    /// name. If not configured, the jurisdiction generates a new key every time it starts, and
+    /// wallets and verifiers, which pin the key they first fetched, reject its accreditations
+    /// until they restart too.
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+}
+
+impl Configuration for JurisdictionConfiguration {
+    fn common_configuration(&self) -> &CommonConfiguration {
+        &self.common
+    }
+}
+
+#[derive(Debug)]
+struct Jurisdiction {
+    config: JurisdictionConfiguration,
+    actor_name: String,
+    bbs_keypair: BbsKeypair,
+}
+
+/// Construct a jurisdiction from its configuration. Returns the actor's name and the routes it
+/// serves.
+pub fn actor(
+    config: JurisdictionConfiguration,
+    _client_builder: ClientBuilder,
+) -> Result<(String, Router), anyhow::Error> {
+    let actor_name = format!("jurisdiction/{}", config.label);
+
+    let bbs_keypair = match &config.key_file {
+        Some(key_file) => BbsKeypair::read_or_generate(key_file)?,
+        None => BbsKeypair::generate()?,
+    };
+    let jurisdiction = Jurisdiction {
+        config,
+        actor_name: actor_name.clone(),
+        bbs_keypair,
+    };
+
+    let routes = Router::new()
+        .route("/config", get(serve_config))
+        .route("/public-key", get(serve_public_key))
+        .route("/accreditations", get(serve_accreditations))
+        .with_state(Arc::new(jurisdiction));
+
+    Ok((actor_name, routes))
+}
+
+/// Print the configuration.
+async fn serve_config(
+    State(jurisdiction): State<Arc<Jurisdiction>>,
+) -> Json<JurisdictionConfiguration> {
+    Json(jurisdiction.config.clone())
+}
+
+/// Publish the jurisdiction's public key, so wallets and verifiers can check its accreditations.
+async fn serve_public_key(
+    State(jurisdiction): State<Arc<Jurisdiction>>,
+) -> Json<JurisdictionPublicKey> {
+    Json(JurisdictionPublicKey {
+        jurisdiction: jurisdiction.actor_name.clone(),
+        public_key: jurisdiction.bbs_keypair.public_key(),
+    })
+}
+
+/// Publish the jurisdiction's accreditations, so wallets and verifiers can check that issuers are
+/// accredited to issue the credentials they issued.
+async fn serve_accreditations(
+    State(jurisdiction): State<Arc<Jurisdiction>>,
+) -> Result<Json<SignedAccreditations>, AppError> {
+    Ok(Json(accreditation::sign(
+        &jurisdiction.actor_name,
+        &jurisdiction.config.accreditations,
+        &jurisdiction.bbs_keypair,
+    )?))
+}
@@ -1 +1,367 @@
+//! Simulation of a person in a digital identity deployment.
 
+use anyhow::Context;
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, post, put},
+};
+use didemo_common::{
+    config::{CommonConfiguration, Configuration},
+    credential::{Address, CredentialType, DriversLicenseRequest, LibraryCardRequest},
+    error::{Error, send},
+    messages::{
+        issuer::{IssueCredentialRequest, RenewCredentialRequest},
+        person::{
+            ConsentRecord, ObtainCredentialRequest, ObtainRenewalRequest, Proof, ProofRequest,
+        },
+    },
+    router::AppError,
+    time::now,
+};
+use reqwest::{Client, ClientBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Configuration for a person.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersonConfiguration {
+    #[serde(flatten)]
+    pub common: CommonConfiguration,
+
+    /// The person's name.
+    pub name: String,
+
+    /// The person's home address.
+    pub home_address: Address,
+
+    /// Whether the person is an organ donor.
+    pub organ_donor: bool,
+
+    /// The person's birthdate, as seconds since the Unix epoch.
+    pub birthdate: u64,
+
+    /// The hostname at which this person's wallet can be reached.
+    // TODO: this should be dynamically settable using some kind of route simulating a wallet
+    // purchase.
+    pub wallet_hostname: String,
+
+    /// Which attributes the person is willing to disclose to which verifiers. By default, the
+    /// person discloses anything to anyone.
+    #[serde(default)]
+    pub disclosure_policy: DisclosurePolicy,
+}
+
+impl Configuration for PersonConfiguration {
+    fn common_configuration(&self) -> &CommonConfiguration {
+        &self.common
+    }
+}
+
+/// A person's policy on disclosing attributes to verifiers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisclosurePolicy {
+    /// Rules governing disclosure. The first rule that applies to an attribute and verifier
+    /// decides whether to disclose that attribute to that verifier.
+    #[serde(default)]
+    pub rules: Vec<DisclosureRule>,
+
+    /// Whether to disclose attributes that no rule applies to.
+    #[serde(default)]
+    pub default: Consent,
+
+    /// Probability that the person approves a disclosure when prompted, between 0 and 1.
+    #[serde(
+        default = "default_approval_rate",
+        deserialize_with = "deserialize_approval_rate"
+    )]
+    pub approval_rate: f64,
+}
+
+fn default_approval_rate() -> f64 {
+    1.0
+}
+
+fn deserialize_approval_rate<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<f64, D::Error> {
+    let approval_rate = f64::deserialize(deserializer)?;
+    // This also rejects NaN.
+    if !(0.0..=1.0).contains(&approval_rate) {
+        return Err(serde::de::Error::custom(format!(
+            "approval_rate must be between 0 and 1, not {approval_rate}"
+        )));
+    }
+
+    Ok(approval_rate)
+}
+
+impl Default for DisclosurePolicy {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            default: Consent::default(),
+            approval_rate: default_approval_rate(),
+        }
+    }
+}
+
+impl DisclosurePolicy {
+    /// Whether to disclose the attributes to the verifier. If several attributes are to be
+    /// disclosed, the most restrictive consent for any of them applies. A proof that discloses no
+    /// attributes still reveals that the person holds a credential, and is governed by the rules
+    /// that apply to all attributes.
+    pub fn consent(&self, verifier: &str, attributes: &[String]) -> Consent {
+        let consent = |attribute: Option<&str>| {
+            self.rules
+                .iter()
+                .find(|rule| rule.applies(verifier, attribute))
+                .map_or(self.default, |rule| rule.consent)
+        };
+
+        if attributes.is_empty() {
+            return consent(None);
+        }
+        attributes
+            .iter()
+            .map(|attribute| consent(Some(attribute)))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// A rule governing whether a person discloses attributes to verifiers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisclosureRule {
+    /// The verifiers the rule applies to, e.g. `relying-party/bar-1`. If empty, the rule applies to
+    /// every verifier.
+    #[serde(default)]
+    pub verifiers: Vec<String>,
+
+    /// Names of the attributes the rule applies to, as in
+    /// [`didemo_common::credential::CredentialType::attributes`]. If empty, the rule applies to
+    /// every attribute.
+    #[serde(default)]
+    pub attributes: Vec<String>,
+
+    /// Whether to disclose the attributes to the verifiers.
+    pub consent: Consent,
+}
+
+impl DisclosureRule {
+    /// Whether the rule applies to disclosing the attribute to the verifier. No attribute stands
+    /// for a proof that discloses none.
+    fn applies(&self, verifier: &str, attribute: Option<&str>) -> bool {
+        let verifier_matches =
+            self.verifiers.is_empty() || self.verifiers.iter().any(|v| v == verifier);
+        let attribute_matches = match attribute {
+            Some(attribute) => {
+                self.attributes.is_empty() || self.attributes.iter().any(|a| a == attribute)
+            }
+            None => self.attributes.is_empty(),
+        };
+
+        verifier_matches && attribute_matches
+    }
+}
+
+/// Whether a person consents to disclosing something, in order of increasing restrictiveness.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Consent {
+    /// Disclose without asking.
+    #[default]
+    Approve,
+    /// Prompt the person, who approves with the policy's approval rate.
+    Ask,
+    /// Refuse to disclose.
+    Deny,
+}
+
+#[derive(Debug)]
+struct Person {
+    /// The person's configuration, which changes as their details do, e.g. when they move.
+    config: Mutex<PersonConfiguration>,
+    http_client: Client,
+    /// Every consent decision the person made, in order.
+    consents: Mutex<Vec<ConsentRecord>>,
+}
+
+/// Construct a person from its configuration. Returns the actor's name and the routes it serves.
+pub fn actor(
+    config: PersonConfiguration,
+    client_builder: ClientBuilder,
+) -> Result<(String, Router), anyhow::Error> {
+    let actor_name = format!("person/{}", config.name);
+
+    Ok((actor_name, routes(config, client_builder.build()?)))
+}
+
+/// Construct the routes a person serves, given the HTTP client with which they make requests.
+pub fn routes(config: PersonConfiguration, http_client: Client) -> Router {
+    Router::new()
+        .route("/config", get(serve_config))
+        .route("/credential", post(obtain_credential))
+        .route("/renewal", post(obtain_renewal))
+        .route("/home-address", put(move_home))
+        .route("/proof", get(prove))
+        .route("/consents", get(consents))
+        .with_state(Arc::new(Person {
+            config: Mutex::new(config),
+            http_client,
+            consents: Mutex::new(Vec::new()),
+        }))
+}
+
+/// Print the configuration.
+async fn serve_config(State(person): State<Arc<Person>>) -> Json<PersonConfiguration> {
+    Json(person.config.lock().unwrap().clone())
+}
+
+/// Print every consent decision the person made.
+async fn consents(State(person): State<Arc<Person>>) -> Json<Vec<ConsentRecord>> {
+    Json(person.consents.lock().unwrap().clone())
+}
+
+/// Instruct the person to obtain a credential from the designated issuer
+#[axum::debug_handler]
+async fn obtain_credential(
+    State(person): State<Arc<Person>>,
+    Json(request): Json<ObtainCredentialRequest>,
+) -> Result<StatusCode, AppError> {
+    let issue_request = person.issue_request(request.credential_type)?;
+
+    // Relay any error from the issuer (or from the wallet, via the issuer) to our caller.
+    send(
+        &request.issuer,
+        person
+            .http_client
+            .put(format!("http://{}/issue", request.issuer))
+            .json(&issue_request),
+    )
+    .await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// Instruct the person to renew a credential they hold, so that it reflects their current details.
+#[axum::debug_handler]
+async fn obtain_renewal(
+    State(person): State<Arc<Person>>,
+    Json(request): Json<ObtainRenewalRequest>,
+) -> Result<StatusCode, AppError> {
+    let renew_request = RenewCredentialRequest {
+        serial_number: request.serial_number,
+        issue_request: person.issue_request(request.credential_type)?,
+    };
+
+    send(
+        &request.issuer,
+        person
+            .http_client
+            .put(format!("http://{}/renew", request.issuer))
+            .json(&renew_request),
+    )
+    .await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// Move the person to a new home. Credentials they already hold keep the old address until
+/// renewed.
+async fn move_home(State(person): State<Arc<Person>>, Json(home_address): Json<Address>) {
+    tracing::info!(%home_address, "moved home");
+    person.config.lock().unwrap().home_address = home_address;
+}
+
+impl Person {
+    /// Request a credential of the type reflecting the person's current details.
+    fn issue_request(
+        &self,
+        credential_type: CredentialType,
+    ) -> Result<IssueCredentialRequest, anyhow::Error> {
+        let config = self.config.lock().unwrap().clone();
+        Ok(IssueCredentialRequest {
+            credential_type,
+            requested_credential: match credential_type {
+                CredentialType::LibraryCard => serde_json::to_string(&LibraryCardRequest {
+                    holder_name: config.name,
+                })
+                .context("failed to serialize credential")?,
+                CredentialType::DriversLicense => serde_json::to_string(&DriversLicenseRequest {
+                    holder_name: config.name,
+                    home_address: config.home_address,
+                    organ_donor: config.organ_donor,
+                    birthdate: config.birthdate,
+                })
+                .context("failed to serialize credential")?,
+            },
+            wallet_hostname: config.wallet_hostname,
+        })
+    }
+}
+
+/// Prove to a verifier that a message is signed, if the person consents to disclosing what the
+/// proof would disclose.
+#[axum::debug_handler]
+async fn prove(
+    State(person): State<Arc<Person>>,
+    Json(proof_request): Json<ProofRequest>,
+) -> Result<Json<Proof>, AppError> {
+    let attributes = proof_request.disclosed_attributes();
+    let (policy, wallet_hostname) = {
+        let config = person.config.lock().unwrap();
+        (
+            config.disclosure_policy.clone(),
+            config.wallet_hostname.clone(),
+        )
+    };
+    let consent = policy.consent(&proof_request.verifier, &attributes);
+    let approved = match consent {
+        Consent::Approve => true,
+        Consent::Ask => rand::random_bool(policy.approval_rate),
+        Consent::Deny => false,
+    };
+
+    tracing::info!(
+        verifier = proof_request.verifier,
+        ?attributes,
+        ?consent,
+        approved,
+        "decided whether to consent to disclosure"
+    );
+    person.consents.lock().unwrap().push(ConsentRecord {
+        verifier: proof_request.verifier.clone(),
+        proof_type: proof_request.proof_type.clone(),
+        attributes: attributes.clone(),
+        prompted: consent == Consent::Ask,
+        approved,
+        decided_at: now()?,
+    });
+
+    if !approved {
+        return Err(Error::NotPermitted {
+            message: format!(
+                "person declined to disclose {attributes:?} to {}",
+                proof_request.verifier
+            ),
+        }
+        .into());
+    }
+
+    // We don't really need to parse the wallet's response since we're just going to send it along
+    // unmodified but it's nice to validate the encoding, I suppose.
+    let proof = send(
+        &wallet_hostname,
+        person
+            .http_client
+            .get(format!("http://{wallet_hostname}/proof"))
+            .json(&proof_request),
+    )
+    .await?
+    .json()
+    .await
+    .context("failed to deserialize proof from wallet")?;
+
+    Ok(Json(proof))
+}
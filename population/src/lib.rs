@@ -0,0 +1,313 @@
+//! Simulation of a population of persons in a digital identity deployment.
+
+use anyhow::Context;
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, post},
+};
+use didemo_common::{
+    config::{CommonConfiguration, Configuration},
+    credential::Address,
+    error::{Error, send},
+    messages::{
+        person::ObtainCredentialRequest,
+        population::{PopulationAccessRequest, PopulationStatistics},
+        relying_party::{AccessDecision, AccessRequest},
+    },
+    router::AppError,
+    storage::StorageConfiguration,
+};
+use didemo_person::{DisclosurePolicy, PersonConfiguration};
+use didemo_wallet::WalletConfiguration;
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+use reqwest::{Client, ClientBuilder};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+
+/// Configuration for a population of persons.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopulationConfiguration {
+    #[serde(flatten)]
+    pub common: CommonConfiguration,
+
+    /// The hostname at which other actors can reach the population.
+    pub hostname: String,
+
+    /// Number of persons in the population.
+    pub size: usize,
+
+    /// Seed from which the persons' attributes are generated. The same seed always generates the
+    /// same persons.
+    #[serde(default)]
+    pub seed: u64,
+
+    /// Configuration for each person's wallet. Wallets keep their credentials and holder secrets in
+    /// memory, so the storage configured here is ignored, as is the hostname.
+    pub wallet: WalletConfiguration,
+
+    /// The policy every person in the population follows when asked to disclose attributes.
+    #[serde(default)]
+    pub disclosure_policy: DisclosurePolicy,
+}
+
+impl Configuration for PopulationConfiguration {
+    fn common_configuration(&self) -> &CommonConfiguration {
+        &self.common
+    }
+}
+
+impl PopulationConfiguration {
+    /// The hostname at which the person with the index can be reached.
+    pub fn person_hostname(&self, index: usize) -> String {
+        format!("{}/persons/{index}", self.hostname)
+    }
+
+    /// The hostname at which the wallet of the person with the index can be reached.
+    pub fn wallet_hostname(&self, index: usize) -> String {
+        format!("{}/wallets/{index}", self.hostname)
+    }
+
+    /// Generate the persons in the population from the seed.
+    pub fn persons(&self) -> Vec<PersonConfiguration> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        (0..self.size)
+            .map(|index| PersonConfiguration {
+                common: self.common.clone(),
+                name: format!(
+                    "{} {}",
+                    FIRST_NAMES.choose(&mut rng).unwrap(),
+                    LAST_NAMES.choose(&mut rng).unwrap()
+                ),
+                home_address: {
+                    let street = format!(
+                        "{} {}",
+                        rng.random_range(1..1000),
+                        STREETS.choose(&mut rng).unwrap()
+                    );
+                    let (city, region, postal_code) = CITIES.choose(&mut rng).unwrap();
+                    Address {
+                        street,
+                        city: city.to_string(),
+                        region: region.to_string(),
+                        postal_code: postal_code.to_string(),
+                        country: "US".to_string(),
+                    }
+                },
+                organ_donor: rng.random_bool(0.5),
+                birthdate: rng.random_range(EARLIEST_BIRTHDATE..LATEST_BIRTHDATE),
+                wallet_hostname: self.wallet_hostname(index),
+                disclosure_policy: self.disclosure_policy.clone(),
+            })
+            .collect()
+    }
+}
+
+const FIRST_NAMES: &[&str] = &[
+    "Abe", "Apu", "Barney", "Bart", "Carl", "Edna", "Homer", "Lenny", "Lisa", "Maggie", "Marge",
+    "Milhouse", "Moe", "Ned", "Patty", "Selma",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Bouvier",
+    "Carlson",
+    "Flanders",
+    "Gumble",
+    "Hibbert",
+    "Krabappel",
+    "Leonard",
+    "Lovejoy",
+    "Muntz",
+    "Simpson",
+    "Skinner",
+    "Szyslak",
+    "Terwilliger",
+    "Van Houten",
+    "Wiggum",
+];
+
+const STREETS: &[&str] = &[
+    "Evergreen Terrace",
+    "Main Street",
+    "Elm Street",
+    "Pennsylvania Avenue",
+    "Walnut Street",
+    "Spalding Way",
+];
+
+/// City, region and postal code of the persons' home addresses.
+const CITIES: &[(&str, &str, &str)] = &[
+    ("Springfield", "OH", "45501"),
+    ("Shelbyville", "OH", "45502"),
+    ("Capital City", "OH", "43215"),
+    ("Ogdenville", "IL", "62701"),
+    ("North Haverbrook", "IL", "62702"),
+];
+
+/// 1970-01-01, in seconds since the UNIX epoch.
+const EARLIEST_BIRTHDATE: u64 = 0;
+
+/// 2010-01-01, in seconds since the UNIX epoch. Some persons are minors for a good while yet.
+const LATEST_BIRTHDATE: u64 = 1262304000;
+
+/// Construct a population from its configuration. Returns the actor's name and the routes it
+/// serves.
+pub fn actor(
+    config: PopulationConfiguration,
+    client_builder: ClientBuilder,
+) -> Result<(String, Router), anyhow::Error> {
+    // Persons and wallets all share one client, and so its connection pool.
+    let http_client = client_builder.build()?;
+
+    let mut routes = Router::new()
+        .route("/config", get(serve_config))
+        .route("/persons", get(persons))
+        .route("/credential", post(obtain_credential))
+        .route("/access", post(request_access))
+        .with_state((config.clone(), http_client.clone()));
+
+    for (index, person) in config.persons().into_iter().enumerate() {
+        let mut wallet = config.wallet.clone();
+        wallet.storage = StorageConfiguration::Memory;
+        wallet.secret_storage = StorageConfiguration::Memory;
+        wallet.hostname = Some(config.wallet_hostname(index));
+
+        routes = routes
+            .nest(
+                &format!("/persons/{index}"),
+                didemo_person::routes(person, http_client.clone()),
+            )
+            .nest(
+                &format!("/wallets/{index}"),
+                didemo_wallet::routes(wallet, http_client.clone())
+                    .context(format!("failed to construct wallet {index}"))?,
+            );
+    }
+
+    tracing::info!(size = config.size, "generated population");
+
+    Ok(("population".to_string(), routes))
+}
+
+/// Print the configuration.
+async fn serve_config(
+    State((config, _)): State<(PopulationConfiguration, Client)>,
+) -> Json<PopulationConfiguration> {
+    Json(config)
+}
+
+/// Print the hostnames of all the persons in the population.
+async fn persons(
+    State((config, _)): State<(PopulationConfiguration, Client)>,
+) -> Json<Vec<String>> {
+    Json(
+        (0..config.size)
+            .map(|index| config.person_hostname(index))
+            .collect(),
+    )
+}
+
+/// Instruct every person to obtain a credential from the designated issuer.
+async fn obtain_credential(
+    State((config, http_client)): State<(PopulationConfiguration, Client)>,
+    Json(request): Json<ObtainCredentialRequest>,
+) -> Result<Json<PopulationStatistics>, AppError> {
+    let statistics = for_each_person(&config, move |person_hostname| {
+        let http_client = http_client.clone();
+        let request = request.clone();
+        async move {
+            send(
+                &person_hostname,
+                http_client
+                    .post(format!("http://{person_hostname}/credential"))
+                    .json(&request),
+            )
+            .await?;
+            Ok(Vec::new())
+        }
+    })
+    .await?;
+
+    Ok(Json(statistics))
+}
+
+/// Instruct every person to request access from the designated relying party.
+async fn request_access(
+    State((config, http_client)): State<(PopulationConfiguration, Client)>,
+    Json(request): Json<PopulationAccessRequest>,
+) -> Result<Json<PopulationStatistics>, AppError> {
+    let statistics = for_each_person(&config, move |person_hostname| {
+        let http_client = http_client.clone();
+        let relying_party = request.relying_party.clone();
+        async move {
+            let decision: AccessDecision = send(
+                &relying_party,
+                http_client
+                    .post(format!("http://{relying_party}/access"))
+                    .json(&AccessRequest { person_hostname }),
+            )
+            .await?
+            .json()
+            .await
+            .map_err(|error| Error::Upstream {
+                message: format!("failed to deserialize access decision: {error}"),
+                host: relying_party,
+                error: None,
+            })?;
+
+            // Key failures by the proof type as serialized, which is stable, unlike its debug
+            // representation.
+            decision
+                .proof_outcomes
+                .into_iter()
+                .filter(|outcome| outcome.failure.is_some())
+                .map(|outcome| {
+                    serde_json::to_string(&outcome.proof_type).map_err(|error| Error::Internal {
+                        message: format!("failed to serialize proof type: {error}"),
+                    })
+                })
+                .collect()
+        }
+    })
+    .await?;
+
+    Ok(Json(statistics))
+}
+
+/// Concurrently do something on behalf of every person in the population, given their hostname,
+/// and tally up the outcomes. The action either fails with an error, or yields the reasons it
+/// failed for, which are none if it succeeded.
+async fn for_each_person<F, Fut>(
+    config: &PopulationConfiguration,
+    mut action: F,
+) -> Result<PopulationStatistics, anyhow::Error>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<Vec<String>, Error>> + Send + 'static,
+{
+    let mut tasks = JoinSet::new();
+    for index in 0..config.size {
+        tasks.spawn(action(config.person_hostname(index)));
+    }
+
+    let mut statistics = PopulationStatistics::default();
+    while let Some(outcome) = tasks.join_next().await {
+        statistics.attempts += 1;
+        match outcome.context("task failed")? {
+            Ok(reasons) if reasons.is_empty() => statistics.successes += 1,
+            Ok(reasons) => {
+                for reason in reasons {
+                    *statistics.failures.entry(reason).or_default() += 1;
+                }
+            }
+            Err(error) => {
+                *statistics
+                    .failures
+                    .entry(error.root_cause().code().to_string())
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    Ok(statistics)
+}
@@ -0,0 +1,286 @@
+//! Simulation of a relying party in a digital identity deployment.
+
+use anyhow::{Context, anyhow};
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, post},
+};
+use didemo_common::{
+    config::{CommonConfiguration, Configuration},
+    error::send,
+    messages::{
+        person::{Proof, ProofRequest, ProofType},
+        relying_party::{AccessDecision, AccessRequest, ProofOutcome},
+    },
+    router::AppError,
+    time::now,
+    trust::{DEFAULT_MAX_STATUS_LIST_AGE, TrustRegistry, TrustedIssuer, TrustedJurisdiction},
+};
+use reqwest::{Client, ClientBuilder};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Configuration for a relying party.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelyingPartyConfiguration {
+    #[serde(flatten)]
+    pub common: CommonConfiguration,
+
+    /// A label identifying the relying party.
+    pub label: String,
+
+    /// Proofs a person must present before this relying party grants them access.
+    pub required_proofs: Vec<ProofType>,
+
+    /// Issuers whose credentials this relying party accepts as proof.
+    pub trusted_issuers: Vec<TrustedIssuer>,
+
+    /// Jurisdictions trusted to accredit issuers. If any are configured, the relying party only
+    /// accepts proofs from credentials whose issuers are accredited by one of them to issue that
+    /// type of credential.
+    #[serde(default)]
+    pub trusted_jurisdictions: Vec<TrustedJurisdiction>,
+
+    /// Whether to check that credentials haven't been revoked. This requires persons to disclose
+    /// their credentials' status list indices.
+    #[serde(default)]
+    pub check_revocation: bool,
+
+    /// Whether to check that credentials haven't expired. This requires persons to disclose when
+    /// their credentials expire, though not when they became valid.
+    #[serde(default)]
+    pub check_validity: bool,
+
+    /// How old, in seconds, an issuer's status list may be when checking revocation.
+    #[serde(default = "default_max_status_list_age")]
+    pub max_status_list_age: u64,
+}
+
+fn default_max_status_list_age() -> u64 {
+    DEFAULT_MAX_STATUS_LIST_AGE
+}
+
+impl Configuration for RelyingPartyConfiguration {
+    fn common_configuration(&self) -> &CommonConfiguration {
+        &self.common
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RelyingParty {
+    config: RelyingPartyConfiguration,
+    actor_name: String,
+    http_client: Client,
+    trust_registry: TrustRegistry,
+    decisions: Vec<AccessDecision>,
+}
+
+/// Construct a relying party from its configuration. Returns the actor's name and the routes it serves.
+pub fn actor(
+    config: RelyingPartyConfiguration,
+    client_builder: ClientBuilder,
+) -> Result<(String, Router), anyhow::Error> {
+    let http_client = client_builder.build()?;
+
+    let actor_name = format!("relying-party/{}", config.label);
+
+    let relying_party = RelyingParty {
+        trust_registry: TrustRegistry::new(config.trusted_issuers.clone(), http_client.clone())
+            .with_jurisdictions(config.trusted_jurisdictions.clone())
+            .with_max_status_list_age(config.max_status_list_age),
+        config,
+        actor_name: actor_name.clone(),
+        http_client,
+        decisions: Vec::new(),
+    };
+
+    let routes = Router::new()
+        .route("/config", get(serve_config))
+        .route("/access", post(grant_access))
+        .route("/decisions", get(decisions))
+        .with_state(Arc::new(Mutex::new(relying_party)));
+
+    Ok((actor_name, routes))
+}
+
+/// Print the configuration.
+async fn serve_config(
+    State(relying_party): State<Arc<Mutex<RelyingParty>>>,
+) -> Json<RelyingPartyConfiguration> {
+    Json(relying_party.lock().unwrap().config.clone())
+}
+
+/// Print all the access decisions this relying party has made.
+async fn decisions(
+    State(relying_party): State<Arc<Mutex<RelyingParty>>>,
+) -> Json<Vec<AccessDecision>> {
+    Json(relying_party.lock().unwrap().decisions.clone())
+}
+
+/// Request each required proof from the person, verify them and decide whether to grant access.
+#[axum::debug_handler]
+async fn grant_access(
+    State(relying_party): State<Arc<Mutex<RelyingParty>>>,
+    Json(request): Json<AccessRequest>,
+) -> Result<Json<AccessDecision>, AppError> {
+    // Don't hold the lock across requests to the person.
+    let (
+        required_proofs,
+        check_revocation,
+        check_validity,
+        actor_name,
+        http_client,
+        trust_registry,
+    ) = {
+        let relying_party = relying_party.lock().unwrap();
+        (
+            relying_party.config.required_proofs.clone(),
+            relying_party.config.check_revocation,
+            relying_party.config.check_validity,
+            relying_party.actor_name.clone(),
+            relying_party.http_client.clone(),
+            relying_party.trust_registry.clone(),
+        )
+    };
+
+    let mut proof_outcomes = Vec::new();
+    for proof_type in required_proofs {
+        // Each proof is bound to this relying party and a fresh nonce so that it can't be replayed.
+        let proof_request = ProofRequest {
+            proof_type: proof_type.clone(),
+            verifier: actor_name.clone(),
+            nonce: rand::random::<[u8; 16]>().to_vec(),
+            revocation_check: check_revocation,
+            validity_check: check_validity,
+        };
+
+        let outcome =
+            match request_proof(&http_client, &request.person_hostname, &proof_request).await {
+                Ok(proof) => match verify_proof(&trust_registry, &proof, &proof_request).await {
+                    Ok(()) => ProofOutcome {
+                        proof_type,
+                        credential_type: proof.credential_type().ok(),
+                        header: Some(proof.header),
+                        proof: Some(proof.proof),
+                        disclosed_messages: proof.disclosed_messages,
+                        failure: None,
+                    },
+                    Err(error) => ProofOutcome {
+                        proof_type,
+                        credential_type: proof.credential_type().ok(),
+                        header: Some(proof.header),
+                        proof: Some(proof.proof),
+                        disclosed_messages: Vec::new(),
+                        failure: Some(format!("{error:#}")),
+                    },
+                },
+                Err(error) => ProofOutcome {
+                    proof_type,
+                    credential_type: None,
+                    header: None,
+                    proof: None,
+                    disclosed_messages: Vec::new(),
+                    failure: Some(format!("{error:#}")),
+                },
+            };
+
+        proof_outcomes.push(outcome);
+    }
+
+    let decision = AccessDecision {
+        person_hostname: request.person_hostname,
+        granted: proof_outcomes
+            .iter()
+            .all(|outcome| outcome.failure.is_none()),
+        decided_at: now()?,
+        proof_outcomes,
+    };
+
+    tracing::info!(
+        person_hostname = decision.person_hostname,
+        granted = decision.granted,
+        "decided access request"
+    );
+
+    relying_party
+        .lock()
+        .unwrap()
+        .decisions
+        .push(decision.clone());
+
+    Ok(Json(decision))
+}
+
+/// Ask the person for a proof.
+async fn request_proof(
+    http_client: &Client,
+    person_hostname: &str,
+    proof_request: &ProofRequest,
+) -> Result<Proof, anyhow::Error> {
+    send(
+        person_hostname,
+        http_client
+            .get(format!("http://{person_hostname}/proof"))
+            .json(proof_request),
+    )
+    .await?
+    .json()
+    .await
+    .context("failed to deserialize proof from person")
+}
+
+/// Verify a proof presented by a person in response to the proof request. Fails if the proof was
+/// not derived from an acceptable type of credential issued by a trusted issuer, accredited by a
+/// trusted jurisdiction if any are configured.
+async fn verify_proof(
+    trust_registry: &TrustRegistry,
+    proof: &Proof,
+    proof_request: &ProofRequest,
+) -> Result<(), anyhow::Error> {
+    // The type of credential is signed into it, so verifying the proof verifies the type too.
+    let credential_type = proof.credential_type()?;
+    if !proof_request
+        .proof_type
+        .selection()
+        .accepts(credential_type, &proof.header)
+    {
+        return Err(anyhow!(
+            "proof made from unacceptable {:?} issued by {}",
+            credential_type,
+            String::from_utf8_lossy(&proof.header)
+        ));
+    }
+
+    let issuer_public_key = trust_registry.resolve(&proof.header, &proof.key_id).await?;
+
+    issuer_public_key.verify_proof(
+        proof.header.clone(),
+        proof_request.presentation_header(),
+        proof.disclosed_messages.clone(),
+        proof.proof.clone(),
+    )?;
+    trust_registry
+        .check_accreditation(&proof.header, credential_type)
+        .await?;
+
+    // A valid proof might still disclose something other than what was asked for.
+    let checked_attributes =
+        proof_request.check_disclosed_messages(credential_type, &proof.disclosed_messages)?;
+
+    let now = now()?;
+    if let Some(valid_until) = checked_attributes.valid_until
+        && valid_until <= now
+    {
+        return Err(anyhow!("credential has expired"));
+    }
+
+    if let Some(status_list_index) = checked_attributes.status_list_index {
+        let status_list = trust_registry.status_list(&proof.header).await?;
+        if status_list.is_revoked(status_list_index)? {
+            return Err(anyhow!("credential has been revoked"));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,7 @@
+use didemo_common::router::actor_main;
+use didemo_relying_party::actor;
+
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
+    actor_main(actor).await
+}
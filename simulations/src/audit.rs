@@ -0,0 +1,242 @@
+use crate::harness::{
+    Simulation, obtain_credential, obtain_drivers_license, parse_configuration,
+    person_configuration, request_access,
+};
+use didemo_common::{
+    credential::CredentialType,
+    linkability::{LinkabilityReport, LinkingFeature},
+    messages::{auditor::AuditReport, person::ProofType},
+};
+use didemo_relying_party::RelyingPartyConfiguration;
+use std::collections::BTreeSet;
+
+#[tokio::test]
+async fn attributes_observed() {
+    let simulation = Simulation::sample().unwrap().start().await.unwrap();
+    let client = simulation.client();
+
+    for (credential_type, issuer) in [
+        (CredentialType::DriversLicense, "issuer-dmv"),
+        (CredentialType::LibraryCard, "issuer-library"),
+    ] {
+        obtain_credential(client, "person", credential_type, issuer).await;
+    }
+
+    let decision = request_access(client, "relying-party", "person").await;
+    assert!(decision.granted, "access denied: {decision:?}");
+
+    let report: AuditReport = client
+        .get("http://auditor/report")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let attribute_names = |credential_type: CredentialType| -> BTreeSet<&str> {
+        credential_type
+            .attributes()
+            .iter()
+            .map(|(name, _)| *name)
+            .collect()
+    };
+
+    // Issuers observe everything in the credentials they issue, but nothing else.
+    assert_eq!(
+        report.attribute_names("person", "issuer-dmv"),
+        attribute_names(CredentialType::DriversLicense)
+    );
+    assert_eq!(
+        report.attribute_names("person", "issuer-library"),
+        attribute_names(CredentialType::LibraryCard)
+    );
+
+    // The wallet holds everything, including who issued each credential.
+    let mut wallet_attribute_names = attribute_names(CredentialType::DriversLicense);
+    wallet_attribute_names.extend(attribute_names(CredentialType::LibraryCard));
+    wallet_attribute_names.insert("issuer");
+    assert_eq!(
+        report.attribute_names("person", "wallet"),
+        wallet_attribute_names
+    );
+
+    // The relying party only learns what it asked for, plus the status list index and expiry it
+    // needs to check revocation and validity, and which issuer and type of credential it's
+    // trusting. It never learns when the credential became valid, let alone exactly when it was
+    // issued.
+    assert_eq!(
+        report.attribute_names("person", "relying-party"),
+        BTreeSet::from([
+            "issuer",
+            "credential_type",
+            "holder_name",
+            "age_over_21",
+            "status_list_index",
+            "valid_until"
+        ])
+    );
+    let relying_party_observations = &report.persons["person"]["relying-party"];
+    assert!(relying_party_observations.iter().any(|attribute| {
+        attribute.name == "holder_name" && attribute.value.as_deref() == Some("Homer Simpson")
+    }));
+}
+
+#[tokio::test]
+async fn linkability() {
+    let bar = |label: &str| -> RelyingPartyConfiguration {
+        parse_configuration(&format!(
+            r#"
+label: "{label}"
+required_proofs:
+  - "HoldsDriversLicense"
+  - "HolderName"
+  - !AgeOver
+    threshold: 21
+trusted_issuers:
+  - name: "issuer/dmv-1"
+    hostname: "issuer-dmv"
+check_revocation: true
+"#
+        ))
+        .unwrap()
+    };
+    let liquor_store: RelyingPartyConfiguration = parse_configuration(
+        r#"
+label: "liquor-store"
+required_proofs:
+  - "HoldsDriversLicense"
+  - !AgeOver
+    threshold: 21
+trusted_issuers:
+  - name: "issuer/dmv-1"
+    hostname: "issuer-dmv"
+"#,
+    )
+    .unwrap();
+    let car_rental: RelyingPartyConfiguration = parse_configuration(
+        r#"
+label: "car-rental"
+required_proofs:
+  - "HoldsDriversLicense"
+  - !AgeOver
+    threshold: 21
+trusted_issuers:
+  - name: "issuer/dmv-1"
+    hostname: "issuer-dmv"
+check_validity: true
+"#,
+    )
+    .unwrap();
+
+    let mut marge = person_configuration("Marge Simpson", "wallet-marge");
+    marge.organ_donor = false;
+
+    let simulation = Simulation::sample()
+        .unwrap()
+        .actor("marge", marge, didemo_person::actor)
+        .actor(
+            "wallet-marge",
+            parse_configuration(
+                r#"
+vendor: "didemo-wallet-maker"
+hostname: wallet-marge
+wallet_maker: wallet-maker
+trusted_issuers:
+  - name: "issuer/dmv-1"
+    hostname: "issuer-dmv"
+"#,
+            )
+            .unwrap(),
+            didemo_wallet::actor,
+        )
+        .actor("bar-1", bar("bar-1"), didemo_relying_party::actor)
+        .actor("bar-2", bar("bar-2"), didemo_relying_party::actor)
+        .actor("liquor-store", liquor_store, didemo_relying_party::actor)
+        .actor("car-rental", car_rental, didemo_relying_party::actor)
+        .actor(
+            "linkability-auditor",
+            parse_configuration(
+                r#"
+persons: [person, marge]
+relying_parties: [bar-1, bar-2, liquor-store, car-rental]
+"#,
+            )
+            .unwrap(),
+            didemo_auditor::actor,
+        )
+        .start()
+        .await
+        .unwrap();
+    let client = simulation.client();
+
+    for person in ["person", "marge"] {
+        obtain_drivers_license(client, person).await;
+
+        for relying_party in ["bar-1", "bar-2", "liquor-store", "car-rental"] {
+            let decision = request_access(client, relying_party, person).await;
+            assert!(decision.granted, "access denied: {decision:?}");
+        }
+    }
+
+    let report: LinkabilityReport = client
+        .get("http://linkability-auditor/linkability")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(report.persons, 2);
+
+    let age_over_21 = ProofType::AgeOver { threshold: 21 };
+    let status_list_index = LinkingFeature::DisclosedMessage {
+        name: "status_list_index".to_string(),
+    };
+    let holder_name = LinkingFeature::DisclosedMessage {
+        name: "holder_name".to_string(),
+    };
+
+    // Checking revocation discloses the credential's status list index, which is as good as a
+    // serial number, so the bars can link every proof.
+    for relying_party in ["bar-1", "bar-2"] {
+        for (proof_type, linked_by) in [
+            (
+                ProofType::HoldsDriversLicense,
+                BTreeSet::from([status_list_index.clone()]),
+            ),
+            (
+                ProofType::HolderName,
+                BTreeSet::from([holder_name.clone(), status_list_index.clone()]),
+            ),
+            (
+                age_over_21.clone(),
+                BTreeSet::from([status_list_index.clone()]),
+            ),
+        ] {
+            let linkability = report.get(relying_party, &proof_type).unwrap();
+            assert_eq!(linkability.presentations, 2);
+            assert_eq!(linkability.linked_presentations, 2);
+            assert_eq!(
+                linkability.linked_by, linked_by,
+                "{relying_party} {proof_type:?}"
+            );
+        }
+    }
+
+    // The liquor store learns nothing but that the persons are over 21, which they both are, and
+    // that they were issued a license by the same issuer.
+    for proof_type in [ProofType::HoldsDriversLicense, age_over_21.clone()] {
+        let linkability = report.get("liquor-store", &proof_type).unwrap();
+        assert_eq!(linkability.presentations, 2);
+        assert!(!linkability.linkable(), "{linkability:?}");
+    }
+
+    // Checking validity discloses when the licenses expire, but licenses issued in the same thirty
+    // day period all expire together, so the car rental can't link proofs by it either.
+    for proof_type in [ProofType::HoldsDriversLicense, age_over_21] {
+        let linkability = report.get("car-rental", &proof_type).unwrap();
+        assert_eq!(linkability.presentations, 2);
+        assert!(!linkability.linkable(), "{linkability:?}");
+    }
+}
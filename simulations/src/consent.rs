@@ -0,0 +1,124 @@
+use crate::harness::{
+    Simulation, obtain_drivers_license, parse_configuration, person_configuration, request_access,
+};
+use didemo_common::messages::person::{ConsentRecord, ProofType};
+use didemo_person::DisclosurePolicy;
+
+#[tokio::test]
+async fn consent_decisions_logged() {
+    // Marge shares Homer's wallet, but is asked before disclosing anybody's name, and refuses.
+    let mut marge = person_configuration("Marge Simpson", "wallet");
+    marge.organ_donor = false;
+    marge.disclosure_policy = parse_configuration(
+        r#"
+rules:
+  - attributes:
+      - "holder_name"
+    consent: ask
+approval_rate: 0.0
+"#,
+    )
+    .unwrap();
+    let simulation = Simulation::sample()
+        .unwrap()
+        .actor("marge", marge, didemo_person::actor)
+        .start()
+        .await
+        .unwrap();
+    let client = simulation.client();
+
+    obtain_drivers_license(client, "person").await;
+
+    // The bar asks for the holder's name, which Marge won't disclose.
+    let decision = request_access(client, "relying-party", "marge").await;
+    assert!(!decision.granted);
+
+    let consents: Vec<ConsentRecord> = client
+        .get("http://marge/consents")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let consents: Vec<_> = consents
+        .into_iter()
+        .map(|consent| {
+            assert_eq!(consent.verifier, "relying-party/bar-1");
+            (
+                consent.proof_type,
+                consent.attributes,
+                consent.prompted,
+                consent.approved,
+            )
+        })
+        .collect();
+    assert_eq!(
+        consents,
+        [
+            (
+                ProofType::HoldsDriversLicense,
+                Vec::from([
+                    "credential_type".to_string(),
+                    "status_list_index".to_string(),
+                    "valid_until".to_string()
+                ]),
+                false,
+                true
+            ),
+            (
+                ProofType::HolderName,
+                Vec::from([
+                    "credential_type".to_string(),
+                    "holder_name".to_string(),
+                    "status_list_index".to_string(),
+                    "valid_until".to_string()
+                ]),
+                true,
+                false
+            ),
+            (
+                ProofType::AgeOver { threshold: 21 },
+                Vec::from([
+                    "credential_type".to_string(),
+                    "age_over_21".to_string(),
+                    "status_list_index".to_string(),
+                    "valid_until".to_string()
+                ]),
+                false,
+                true
+            ),
+        ]
+    );
+
+    // Homer discloses anything to anyone, without being asked.
+    let decision = request_access(client, "relying-party", "person").await;
+    assert!(decision.granted, "access denied: {decision:?}");
+
+    let consents: Vec<ConsentRecord> = client
+        .get("http://person/consents")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(consents.len(), 3);
+    assert!(
+        consents
+            .iter()
+            .all(|consent| consent.approved && !consent.prompted)
+    );
+}
+
+#[test]
+fn approval_rate_out_of_range_rejected() {
+    let policy = |approval_rate: &str| {
+        parse_configuration::<DisclosurePolicy>(&format!("approval_rate: {approval_rate}"))
+    };
+
+    assert_eq!(policy("0.5").unwrap().approval_rate, 0.5);
+    policy(".nan").unwrap_err();
+    policy("1.5").unwrap_err();
+    policy("-0.1").unwrap_err();
+}
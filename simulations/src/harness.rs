@@ -0,0 +1,229 @@
+//! An in-process harness that runs simulation actors in the current tokio runtime, without Docker.
+//!
+//! Each actor listens on an ephemeral port on the loopback interface. The HTTP clients of all the
+//! actors, and the one used to drive the simulation, resolve actor hostnames like `issuer-dmv` to
+//! those ports, so actors address one another just as they do in the compose environment.
+
+use anyhow::Context;
+use axum::Router;
+use didemo_auditor::AuditorConfiguration;
+use didemo_common::{
+    credential::CredentialType,
+    messages::{
+        person::ObtainCredentialRequest,
+        relying_party::{AccessDecision, AccessRequest},
+    },
+    router::client_builder,
+    storage::StorageConfiguration,
+};
+use didemo_issuer::IssuerConfiguration;
+use didemo_jurisdiction::JurisdictionConfiguration;
+use didemo_person::PersonConfiguration;
+use didemo_population::PopulationConfiguration;
+use didemo_relying_party::RelyingPartyConfiguration;
+use didemo_wallet::WalletConfiguration;
+use didemo_wallet_maker::WalletMakerConfiguration;
+use reqwest::{Client, ClientBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, SocketAddr},
+};
+use tokio::{net::TcpListener, task::JoinSet};
+
+/// Constructs an actor's routes, given an HTTP client builder. See the `actor` function in each
+/// actor crate.
+type ActorConstructor =
+    Box<dyn FnOnce(ClientBuilder) -> Result<(String, Router), anyhow::Error> + Send>;
+
+/// Actors to run in a simulation.
+#[derive(Default)]
+pub struct SimulationBuilder {
+    actors: Vec<(String, ActorConstructor)>,
+}
+
+impl SimulationBuilder {
+    /// Add an actor reachable at the hostname, constructed from its configuration by `actor`,
+    /// which is the `actor` function of some actor crate (e.g. `didemo_issuer::actor`).
+    pub fn actor<C: Send + 'static>(
+        mut self,
+        hostname: &str,
+        config: C,
+        actor: fn(C, ClientBuilder) -> Result<(String, Router), anyhow::Error>,
+    ) -> Self {
+        self.actors.push((
+            hostname.to_string(),
+            Box::new(move |client_builder| actor(config, client_builder)),
+        ));
+        self
+    }
+
+    /// Start serving all the actors.
+    pub async fn start(self) -> Result<Simulation, anyhow::Error> {
+        // Bind all the listeners before constructing any actor, so that every actor's client can
+        // resolve every other actor's hostname.
+        let mut listeners = Vec::new();
+        let mut addresses = HashMap::new();
+        for (hostname, _) in &self.actors {
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .await
+                .context(format!("failed to bind listener for {hostname}"))?;
+            addresses.insert(hostname.clone(), listener.local_addr()?);
+            listeners.push(listener);
+        }
+
+        // Actor hostnames only resolve within the simulation, so no proxy could reach them.
+        let resolving_client_builder = || {
+            addresses.iter().fold(
+                client_builder().no_proxy(),
+                |builder, (hostname, address)| builder.resolve(hostname, *address),
+            )
+        };
+
+        let mut actor_tasks = JoinSet::new();
+        for ((hostname, actor), listener) in self.actors.into_iter().zip(listeners) {
+            let (actor_name, routes) = actor(resolving_client_builder())
+                .context(format!("failed to construct actor {hostname}"))?;
+            tracing::debug!(hostname, actor_name, address = ?addresses[&hostname], "started actor");
+            actor_tasks.spawn(async move { axum::serve(listener, routes).await });
+        }
+
+        Ok(Simulation {
+            client: resolving_client_builder().build()?,
+            addresses,
+            _actor_tasks: actor_tasks,
+        })
+    }
+}
+
+/// A running simulation. Its actors stop when it is dropped.
+pub struct Simulation {
+    client: Client,
+    addresses: HashMap<String, SocketAddr>,
+    _actor_tasks: JoinSet<Result<(), io::Error>>,
+}
+
+impl Simulation {
+    /// Construct an empty simulation, to which actors can be added.
+    pub fn builder() -> SimulationBuilder {
+        SimulationBuilder::default()
+    }
+
+    /// Construct the simulation defined by the sample configurations in this repository, which is
+    /// also what the compose environment runs. Unlike in the compose environment, actors keep their
+    /// state in memory and issuers, jurisdictions and wallet makers generate new keys, so each
+    /// simulation starts from scratch.
+    pub fn sample() -> Result<SimulationBuilder, anyhow::Error> {
+        let person = person_configuration("Homer Simpson", "wallet");
+
+        let mut wallet: WalletConfiguration =
+            parse_configuration(include_str!("../../wallet/config/sample.yaml"))?;
+        wallet.storage = StorageConfiguration::Memory;
+        wallet.secret_storage = StorageConfiguration::Memory;
+
+        let mut wallet_maker: WalletMakerConfiguration =
+            parse_configuration(include_str!("../../wallet_maker/config/sample.yaml"))?;
+        wallet_maker.key_file = None;
+
+        let mut issuer_dmv: IssuerConfiguration =
+            parse_configuration(include_str!("../../issuer/config/dmv.yaml"))?;
+        issuer_dmv.storage = StorageConfiguration::Memory;
+        issuer_dmv.key_file = None;
+
+        let mut issuer_library: IssuerConfiguration =
+            parse_configuration(include_str!("../../issuer/config/library.yaml"))?;
+        issuer_library.storage = StorageConfiguration::Memory;
+        issuer_library.key_file = None;
+
+        let mut jurisdiction: JurisdictionConfiguration =
+            parse_configuration(include_str!("../../jurisdiction/config/sample.yaml"))?;
+        jurisdiction.key_file = None;
+
+        let relying_party: RelyingPartyConfiguration =
+            parse_configuration(include_str!("../../relying_party/config/sample.yaml"))?;
+
+        let auditor: AuditorConfiguration =
+            parse_configuration(include_str!("../../auditor/config/sample.yaml"))?;
+
+        let population: PopulationConfiguration =
+            parse_configuration(include_str!("../../population/config/sample.yaml"))?;
+
+        Ok(Self::builder()
+            .actor("person", person, didemo_person::actor)
+            .actor("wallet", wallet, didemo_wallet::actor)
+            .actor("wallet-maker", wallet_maker, didemo_wallet_maker::actor)
+            .actor("issuer-dmv", issuer_dmv, didemo_issuer::actor)
+            .actor("issuer-library", issuer_library, didemo_issuer::actor)
+            .actor("jurisdiction", jurisdiction, didemo_jurisdiction::actor)
+            .actor("relying-party", relying_party, didemo_relying_party::actor)
+            .actor("auditor", auditor, didemo_auditor::actor)
+            .actor("population", population, didemo_population::actor))
+    }
+
+    /// An HTTP client that resolves the hostnames of the simulation's actors.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// The address at which the actor with the hostname is listening.
+    pub fn address(&self, hostname: &str) -> Option<SocketAddr> {
+        self.addresses.get(hostname).copied()
+    }
+}
+
+/// Parse an actor configuration from YAML.
+pub fn parse_configuration<C: DeserializeOwned>(yaml: &str) -> Result<C, anyhow::Error> {
+    serde_yaml::from_str(yaml).context("failed to parse configuration")
+}
+
+/// Configure a person with the name and wallet, who is otherwise the sample person: they live at
+/// 742 Evergreen Terrace, Springfield, OH, are an organ donor, were born in 1980 and disclose
+/// anything to anyone. Tests adjust the rest of the configuration as they need.
+pub fn person_configuration(name: &str, wallet_hostname: &str) -> PersonConfiguration {
+    let mut person: PersonConfiguration =
+        parse_configuration(include_str!("../../person/config/sample.yaml"))
+            .expect("sample person configuration should parse");
+    person.name = name.to_string();
+    person.wallet_hostname = wallet_hostname.to_string();
+    person
+}
+
+/// Instruct the person to obtain a credential of the type from the issuer, and check that they did.
+pub async fn obtain_credential(
+    client: &Client,
+    person: &str,
+    credential_type: CredentialType,
+    issuer: &str,
+) {
+    let response = client
+        .post(format!("http://{person}/credential"))
+        .json(&ObtainCredentialRequest {
+            credential_type,
+            issuer: issuer.to_string(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+/// Instruct the person to obtain a driver's license from the sample DMV.
+pub async fn obtain_drivers_license(client: &Client, person: &str) {
+    obtain_credential(client, person, CredentialType::DriversLicense, "issuer-dmv").await;
+}
+
+/// Ask the relying party to decide whether to grant the person access.
+pub async fn request_access(client: &Client, relying_party: &str, person: &str) -> AccessDecision {
+    client
+        .post(format!("http://{relying_party}/access"))
+        .json(&AccessRequest {
+            person_hostname: person.to_string(),
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
@@ -1,44 +1,57 @@
+use crate::harness::{
+    Simulation, SimulationBuilder, obtain_credential, obtain_drivers_license, parse_configuration,
+    request_access,
+};
 use didemo_common::{
-    bbs::BbsKeypair,
-    credential::{Credential, CredentialType, DriversLicense, LibraryCard},
-    messages::person::{ObtainCredentialRequest, Proof, ProofRequest, ProofType},
+    bbs::{BbsKeypair, HolderKeypair},
+    credential::{
+        Address, Credential, CredentialSignature, CredentialType, DriversLicense, LibraryCard,
+        NEVER_EXPIRES,
+    },
+    error::Error,
+    messages::{
+        issuer::{
+            Delivery, IssueCredentialRequest, IssuerPublicKey, LedgerEntry, LedgerQuery,
+            RevokeCredentialRequest,
+        },
+        person::{ObtainCredentialRequest, Proof, ProofRequest, ProofType},
+        relying_party::AccessDecision,
+        wallet::{HolderBinding, HolderBindingRequest, StoreCredentialRequest},
+    },
+    revocation::SignedStatusList,
+    storage::StorageConfiguration,
+    trust::{TrustRegistry, TrustedIssuer},
 };
-use reqwest::StatusCode;
+use didemo_issuer::IssuerConfiguration;
+use didemo_jurisdiction::JurisdictionConfiguration;
+use didemo_wallet::WalletConfiguration;
+use didemo_wallet_maker::WalletMakerConfiguration;
+use reqwest::{Client, StatusCode};
+use std::{collections::HashSet, env, fs, path::Path, process, time::Duration};
 
 #[tokio::test]
 async fn issue_credential() {
-    let client = reqwest::Client::new();
+    let simulation = Simulation::sample().unwrap().start().await.unwrap();
+    let client = simulation.client();
 
     // Person obtains a driver's license
-    let obtain_drivers_request = ObtainCredentialRequest {
-        credential_type: CredentialType::DriversLicense,
-        issuer: "issuer-dmv".to_string(),
-    };
-    let response = client
-        .post("http://0.0.0.0:8000/credential")
-        .json(&obtain_drivers_request)
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::CREATED);
+    obtain_drivers_license(client, "person").await;
 
     // Person obtains a library card
-    let obtain_library_card_request = ObtainCredentialRequest {
-        credential_type: CredentialType::LibraryCard,
-        issuer: "issuer-library".to_string(),
-    };
-    let response = client
-        .post("http://0.0.0.0:8000/credential")
-        .json(&obtain_library_card_request)
-        .send()
-        .await
-        .unwrap();
-    assert_eq!(response.status(), StatusCode::CREATED);
+    obtain_credential(
+        client,
+        "person",
+        CredentialType::LibraryCard,
+        "issuer-library",
+    )
+    .await;
 
     // Ensure appropriate credentials appear in the wallet and that the signatures verify. Note that
     // we aren't yet doing any privacy preserving proof stuff. Signature verification reveals all
     // the messages to the verifier.
-    let mut wallet_credentials: Vec<Credential> = reqwest::get("http://0.0.0.0:8001/credentials")
+    let mut wallet_credentials: Vec<Credential> = client
+        .get("http://wallet/credentials")
+        .send()
         .await
         .unwrap()
         .json()
@@ -48,6 +61,9 @@ async fn issue_credential() {
 
     let mut saw_drivers = false;
     let mut saw_library = false;
+    // Status list indices are allocated at random, so we can only learn them from the credentials.
+    let mut dl_status_list_index = None;
+    let mut dl_valid_until = None;
     for credential in &wallet_credentials {
         match credential.credential_type {
             CredentialType::LibraryCard => {
@@ -61,6 +77,9 @@ async fn issue_credential() {
                         library_name: "library-1".to_string(),
                         holder_name: "Homer Simpson".to_string(),
                         serial_number: 1,
+                        status_list_index: decoded_credential.status_list_index,
+                        valid_from: decoded_credential.valid_from,
+                        valid_until: decoded_credential.valid_until,
                     },
                 );
             }
@@ -75,22 +94,59 @@ async fn issue_credential() {
                         issuing_jurisdiction: "dmv-1".to_string(),
                         holder_name: "Homer Simpson".to_string(),
                         serial_number: 1,
-                        home_address: "742 Evergreen Terrace, Springfield, OH".to_string(),
+                        home_address: Address {
+                            street: "742 Evergreen Terrace".to_string(),
+                            city: "Springfield".to_string(),
+                            region: "OH".to_string(),
+                            postal_code: "45501".to_string(),
+                            country: "US".to_string(),
+                        },
                         organ_donor: true,
-                        birthdate: 1753729603,
+                        birthdate: 326937600,
+                        // Homer was born in 1980, so he's over 18 and 21 but not 65.
+                        age_over: [true, true, false],
+                        status_list_index: decoded_credential.status_list_index,
+                        valid_from: decoded_credential.valid_from,
+                        valid_until: decoded_credential.valid_until,
                     }
                 );
+                // The DMV issues licenses valid for five years, from the start of the thirty day
+                // period they are issued in until the end of one.
+                let granularity = 2592000;
+                let validity_period =
+                    decoded_credential.valid_until - decoded_credential.valid_from;
+                assert!(
+                    (157680000..157680000 + 2 * granularity).contains(&validity_period),
+                    "{validity_period}"
+                );
+                assert_eq!(decoded_credential.valid_from % granularity, 0);
+                assert_eq!(decoded_credential.valid_until % granularity, 0);
+                dl_status_list_index = Some(decoded_credential.status_list_index);
+                dl_valid_until = Some(decoded_credential.valid_until);
             }
         };
     }
 
+    let dl_status_list_message = (
+        CredentialType::DriversLicense.status_message_index(),
+        dl_status_list_index.unwrap().to_be_bytes().to_vec(),
+    );
+    let dl_valid_until_message = (
+        CredentialType::DriversLicense.valid_until_message_index(),
+        dl_valid_until.unwrap().to_be_bytes().to_vec(),
+    );
+
     // Obtain proof that person holds a driver's license. We learn nothing about the contents of the
     // license, just that one was issued by the issuer identified in the BBS signature header.
     let dl_possession_proof_req = ProofRequest {
         proof_type: ProofType::HoldsDriversLicense,
+        verifier: "simulation".to_string(),
+        nonce: b"dl-possession-nonce".to_vec(),
+        revocation_check: false,
+        validity_check: false,
     };
     let dl_possession_proof: Proof = client
-        .get("http://0.0.0.0:8000/proof")
+        .get("http://person/proof")
         .json(&dl_possession_proof_req)
         .send()
         .await
@@ -99,25 +155,44 @@ async fn issue_credential() {
         .await
         .unwrap();
 
-    let issuer_keypair =
-        BbsKeypair::new(str::from_utf8(&dl_possession_proof.header).unwrap()).unwrap();
+    // Resolve the issuer's public key the same way wallets and relying parties do.
+    let trust_registry = TrustRegistry::new(
+        Vec::from([TrustedIssuer {
+            name: "issuer/dmv-1".to_string(),
+            hostname: "issuer-dmv".to_string(),
+        }]),
+        client.clone(),
+    );
+    let issuer_public_key = trust_registry
+        .resolve(&dl_possession_proof.header, &dl_possession_proof.key_id)
+        .await
+        .unwrap();
 
-    issuer_keypair
+    issuer_public_key
         .verify_proof(
             dl_possession_proof.header,
+            dl_possession_proof_req.presentation_header(),
             dl_possession_proof.disclosed_messages.clone(),
             dl_possession_proof.proof,
         )
         .unwrap();
 
-    assert!(dl_possession_proof.disclosed_messages.is_empty());
+    // The proof discloses nothing but the type of credential it was made from.
+    assert_eq!(
+        dl_possession_proof.disclosed_messages,
+        Vec::from([(0, b"DriversLicense".to_vec())])
+    );
 
     // Obtain proof of the person's name. We learn nothing else from their driver's license.
     let name_proof_req = ProofRequest {
         proof_type: ProofType::HolderName,
+        verifier: "simulation".to_string(),
+        nonce: b"name-nonce".to_vec(),
+        revocation_check: false,
+        validity_check: false,
     };
     let name_proof: Proof = client
-        .get("http://0.0.0.0:8000/proof")
+        .get("http://person/proof")
         .json(&name_proof_req)
         .send()
         .await
@@ -126,20 +201,582 @@ async fn issue_credential() {
         .await
         .unwrap();
 
-    let issuer_keypair = BbsKeypair::new(str::from_utf8(&name_proof.header).unwrap()).unwrap();
+    let issuer_public_key = trust_registry
+        .resolve(&name_proof.header, &name_proof.key_id)
+        .await
+        .unwrap();
 
-    issuer_keypair
+    issuer_public_key
         .verify_proof(
-            name_proof.header,
+            name_proof.header.clone(),
+            name_proof_req.presentation_header(),
             name_proof.disclosed_messages.clone(),
-            name_proof.proof,
+            name_proof.proof.clone(),
         )
         .unwrap();
 
     assert_eq!(
         name_proof.disclosed_messages,
-        Vec::from([(1, "Homer Simpson".as_bytes().to_vec())])
+        Vec::from([
+            (0, b"DriversLicense".to_vec()),
+            (2, b"Homer Simpson".to_vec())
+        ])
+    );
+
+    // A captured proof can't be replayed to a different verifier...
+    let other_verifier_req = ProofRequest {
+        verifier: "some-other-verifier".to_string(),
+        ..name_proof_req.clone()
+    };
+    issuer_public_key
+        .verify_proof(
+            name_proof.header.clone(),
+            other_verifier_req.presentation_header(),
+            name_proof.disclosed_messages.clone(),
+            name_proof.proof.clone(),
+        )
+        .unwrap_err();
+
+    // ...or to the same verifier in a later session, since it will have chosen a fresh nonce.
+    let later_session_req = ProofRequest {
+        nonce: b"later-nonce".to_vec(),
+        ..name_proof_req
+    };
+    issuer_public_key
+        .verify_proof(
+            name_proof.header,
+            later_session_req.presentation_header(),
+            name_proof.disclosed_messages,
+            name_proof.proof,
+        )
+        .unwrap_err();
+
+    // Obtain proof that the person is over 21, without learning their birthdate.
+    let age_proof_req = ProofRequest {
+        proof_type: ProofType::AgeOver { threshold: 21 },
+        verifier: "simulation".to_string(),
+        nonce: b"age-nonce".to_vec(),
+        revocation_check: false,
+        validity_check: false,
+    };
+    let age_proof: Proof = client
+        .get("http://person/proof")
+        .json(&age_proof_req)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    issuer_public_key
+        .verify_proof(
+            age_proof.header.clone(),
+            age_proof_req.presentation_header(),
+            age_proof.disclosed_messages.clone(),
+            age_proof.proof.clone(),
+        )
+        .unwrap();
+    assert_eq!(
+        age_proof.credential_type().unwrap(),
+        CredentialType::DriversLicense
+    );
+    age_proof_req
+        .check_disclosed_messages(
+            age_proof.credential_type().unwrap(),
+            &age_proof.disclosed_messages,
+        )
+        .unwrap();
+
+    // The person is not over 65, so can't prove it.
+    let response = client
+        .get("http://person/proof")
+        .json(&ProofRequest {
+            proof_type: ProofType::AgeOver { threshold: 65 },
+            verifier: "simulation".to_string(),
+            nonce: b"age-65-nonce".to_vec(),
+            revocation_check: false,
+            validity_check: false,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert!(!response.status().is_success());
+
+    // Person visits the relying party, which requires proof of a driver's license, the holder's name
+    // and that the holder is over 21 before granting access. The relying party also checks that the
+    // license hasn't been revoked, so each proof discloses its status list index.
+    let decision = request_access(client, "relying-party", "person").await;
+
+    assert!(decision.granted, "access denied: {decision:?}");
+    assert_eq!(decision.proof_outcomes.len(), 3);
+    let dl_type_message = (CredentialType::MESSAGE_INDEX, b"DriversLicense".to_vec());
+    assert_eq!(
+        decision.proof_outcomes[0].proof_type,
+        ProofType::HoldsDriversLicense
+    );
+    assert_eq!(
+        decision.proof_outcomes[0].disclosed_messages,
+        Vec::from([
+            dl_type_message.clone(),
+            dl_status_list_message.clone(),
+            dl_valid_until_message.clone()
+        ])
+    );
+    assert_eq!(decision.proof_outcomes[1].proof_type, ProofType::HolderName);
+    assert_eq!(
+        decision.proof_outcomes[1].disclosed_messages,
+        Vec::from([
+            dl_type_message.clone(),
+            (2, "Homer Simpson".as_bytes().to_vec()),
+            dl_status_list_message.clone(),
+            dl_valid_until_message.clone()
+        ])
+    );
+    assert_eq!(
+        decision.proof_outcomes[2].proof_type,
+        ProofType::AgeOver { threshold: 21 }
+    );
+    assert_eq!(
+        decision.proof_outcomes[2].disclosed_messages,
+        Vec::from([
+            dl_type_message,
+            (
+                DriversLicense::age_over_message_index(21).unwrap(),
+                Vec::from([1])
+            ),
+            dl_status_list_message,
+            dl_valid_until_message
+        ])
+    );
+
+    // The relying party remembers its decision.
+    let decisions: Vec<AccessDecision> = client
+        .get("http://relying-party/decisions")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(decisions.contains(&decision));
+
+    // The DMV revokes the license, after which the relying party denies the person access.
+    let response = client
+        .post("http://issuer-dmv/revoke")
+        .json(&RevokeCredentialRequest { serial_number: 1 })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let decision = request_access(client, "relying-party", "person").await;
+
+    assert!(!decision.granted);
+    for outcome in decision.proof_outcomes {
+        assert!(
+            outcome
+                .failure
+                .as_ref()
+                .is_some_and(|failure| failure.contains("revoked")),
+            "unexpected outcome: {outcome:?}"
+        );
+    }
+
+    // The DMV's ledger records the license, the wallet it was issued into and its revocation.
+    let ledger: Vec<LedgerEntry> = client
+        .get("http://issuer-dmv/ledger")
+        .query(&LedgerQuery {
+            serial_number: Some(1),
+            ..Default::default()
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(ledger.len(), 1);
+    assert_eq!(ledger[0].credential_type, CredentialType::DriversLicense);
+    assert_eq!(ledger[0].wallet_hostname, "wallet");
+    assert_eq!(Some(ledger[0].status_list_index), dl_status_list_index);
+    assert_eq!(ledger[0].delivery, Delivery::Delivered);
+    assert!(ledger[0].revoked_at.is_some());
+}
+
+/// A person obtaining library cards from a library whose status list has room for
+/// `status_list_size` credentials, keeping its state in the file.
+fn small_library_simulation(storage: &Path, status_list_size: u64) -> SimulationBuilder {
+    let mut wallet: WalletConfiguration =
+        parse_configuration(include_str!("../../wallet/config/sample.yaml")).unwrap();
+    wallet.storage = StorageConfiguration::Memory;
+    wallet.secret_storage = StorageConfiguration::Memory;
+
+    let mut issuer_library: IssuerConfiguration =
+        parse_configuration(include_str!("../../issuer/config/library.yaml")).unwrap();
+    issuer_library.storage = StorageConfiguration::JsonFile {
+        path: storage.to_path_buf(),
+    };
+    issuer_library.key_file = None;
+    issuer_library.status_list_size = status_list_size;
+
+    let mut wallet_maker: WalletMakerConfiguration =
+        parse_configuration(include_str!("../../wallet_maker/config/sample.yaml")).unwrap();
+    wallet_maker.key_file = None;
+
+    let mut jurisdiction: JurisdictionConfiguration =
+        parse_configuration(include_str!("../../jurisdiction/config/sample.yaml")).unwrap();
+    jurisdiction.key_file = None;
+
+    Simulation::builder()
+        .actor(
+            "person",
+            parse_configuration(include_str!("../../person/config/sample.yaml")).unwrap(),
+            didemo_person::actor,
+        )
+        .actor("wallet", wallet, didemo_wallet::actor)
+        .actor("wallet-maker", wallet_maker, didemo_wallet_maker::actor)
+        .actor("issuer-library", issuer_library, didemo_issuer::actor)
+        .actor("jurisdiction", jurisdiction, didemo_jurisdiction::actor)
+}
+
+async fn status_list_len(client: &Client) -> u64 {
+    let signed_status_list: SignedStatusList = client
+        .get("http://issuer-library/status-list")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    signed_status_list.status_list.len()
+}
+
+#[tokio::test]
+async fn status_list_grows_when_full() {
+    let storage = env::temp_dir().join(format!("didemo-status-list-{}.json", process::id()));
+
+    {
+        let simulation = small_library_simulation(&storage, 8).start().await.unwrap();
+        let client = simulation.client();
+        assert_eq!(status_list_len(client).await, 8);
+
+        // The ninth library card doesn't fit in the status list, so the library grows it.
+        for _ in 0..9 {
+            obtain_credential(
+                client,
+                "person",
+                CredentialType::LibraryCard,
+                "issuer-library",
+            )
+            .await;
+        }
+        assert_eq!(status_list_len(client).await, 16);
+
+        let ledger: Vec<LedgerEntry> = client
+            .get("http://issuer-library/ledger")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let status_list_indices: HashSet<_> =
+            ledger.iter().map(|entry| entry.status_list_index).collect();
+        assert_eq!(status_list_indices.len(), 9);
+    }
+
+    // After a restart, the stored status list grows to a larger configured size, but never shrinks.
+    for (status_list_size, expected_len) in [(64, 64), (8, 64)] {
+        let simulation = small_library_simulation(&storage, status_list_size)
+            .start()
+            .await
+            .unwrap();
+        assert_eq!(status_list_len(simulation.client()).await, expected_len);
+    }
+
+    fs::remove_file(storage).unwrap();
+}
+
+#[tokio::test]
+async fn undelivered_credential_revoked() {
+    let simulation = Simulation::sample()
+        .unwrap()
+        .actor(
+            "wallet-misconfigured",
+            parse_configuration::<WalletConfiguration>(
+                r#"
+vendor: "didemo-wallet-maker"
+trusted_issuers:
+  - name: "issuer/library-1"
+    hostname: "issuer-dmv"
+"#,
+            )
+            .unwrap(),
+            didemo_wallet::actor,
+        )
+        .start()
+        .await
+        .unwrap();
+    let client = simulation.client();
+
+    // The wallet looks the library's key up at the wrong host, so it rejects the card.
+    let response = client
+        .put("http://issuer-library/issue")
+        .json(&IssueCredentialRequest {
+            credential_type: CredentialType::LibraryCard,
+            requested_credential: r#"{"holder_name": "Homer Simpson"}"#.to_string(),
+            wallet_hostname: "wallet-misconfigured".to_string(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+    // The library still records the card, which nobody holds, so its serial number is never reused,
+    // but it records that delivery failed and revokes it.
+    let ledger: Vec<LedgerEntry> = client
+        .get("http://issuer-library/ledger")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(ledger.len(), 1);
+    assert_eq!(ledger[0].wallet_hostname, "wallet-misconfigured");
+    assert_eq!(ledger[0].delivery, Delivery::Failed);
+    assert!(ledger[0].revoked_at.is_some());
+}
+
+#[tokio::test]
+async fn forged_credential_rejected() {
+    let simulation = Simulation::sample().unwrap().start().await.unwrap();
+
+    // Knowing the issuer's label used to be enough to derive its signing key. Now that issuers
+    // generate random keys and wallets pin the published key, such a forgery is rejected.
+    let forged_license = DriversLicense {
+        issuing_jurisdiction: "dmv-1".to_string(),
+        holder_name: "Bart Simpson".to_string(),
+        serial_number: 1000,
+        home_address: Address::default(),
+        organ_donor: false,
+        birthdate: 1753729603,
+        age_over: [true, true, true],
+        status_list_index: 0,
+        valid_from: 0,
+        valid_until: NEVER_EXPIRES,
+    };
+    // The forger claims the signature was made with the issuer's current key.
+    let issuer_public_key: IssuerPublicKey = simulation
+        .client()
+        .get("http://issuer-dmv/public-key")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    // The forger gets a holder key from the wallet in the issuer's name and binds the forgery to it.
+    let binding: HolderBinding = simulation
+        .client()
+        .post("http://wallet/holder-bindings")
+        .json(&HolderBindingRequest {
+            issuer: "issuer/dmv-1".to_string(),
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let header = b"issuer/dmv-1".to_vec();
+    let signature = BbsKeypair::new("issuer/dmv-1")
+        .unwrap()
+        .sign_bound(
+            header.clone(),
+            forged_license.bbs_messages(),
+            &binding.public_key,
+        )
+        .unwrap();
+
+    let response = simulation
+        .client()
+        .put("http://wallet/credentials")
+        .json(&StoreCredentialRequest {
+            binding_id: binding.binding_id,
+            credential: Credential {
+                credential_type: CredentialType::DriversLicense,
+                encoded_credential: serde_json::to_string(&forged_license).unwrap(),
+                signature: CredentialSignature {
+                    signature,
+                    header,
+                    key_id: issuer_public_key.key_id,
+                },
+            },
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(matches!(
+        response.json().await.unwrap(),
+        Error::InvalidSignature { .. }
+    ));
+}
+
+#[tokio::test]
+async fn stolen_credential_unusable() {
+    let simulation = Simulation::sample().unwrap().start().await.unwrap();
+    let client = simulation.client();
+
+    obtain_drivers_license(client, "person").await;
+
+    // A thief dumps the credentials out of the wallet.
+    let stolen_credentials: Vec<Credential> = client
+        .get("http://wallet/credentials")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stolen_credentials.len(), 1);
+    let stolen_license = &stolen_credentials[0];
+
+    // Another wallet won't accept the license, even under a binding it handed out in the issuer's
+    // name, since the license is bound to a key only Homer's wallet holds.
+    let binding: HolderBinding = client
+        .post("http://population/wallets/0/holder-bindings")
+        .json(&HolderBindingRequest {
+            issuer: "issuer/dmv-1".to_string(),
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let response = client
+        .put("http://population/wallets/0/credentials")
+        .json(&StoreCredentialRequest {
+            binding_id: binding.binding_id,
+            credential: stolen_license.clone(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(matches!(
+        response.json().await.unwrap(),
+        Error::InvalidSignature { .. }
+    ));
+
+    // Nor can a wallet that skips that check present the license: a proof made with another holder
+    // key doesn't verify.
+    let trust_registry = TrustRegistry::new(
+        Vec::from([TrustedIssuer {
+            name: "issuer/dmv-1".to_string(),
+            hostname: "issuer-dmv".to_string(),
+        }]),
+        client.clone(),
     );
+    let issuer_public_key = trust_registry
+        .resolve(
+            &stolen_license.signature.header,
+            &stolen_license.signature.key_id,
+        )
+        .await
+        .unwrap();
+    let presentation_header = b"stolen-license-nonce".to_vec();
+    let messages = stolen_license
+        .bbs_messages()
+        .unwrap()
+        .into_iter()
+        .map(|message| (false, message))
+        .collect();
+    issuer_public_key
+        .prove(
+            stolen_license.signature.header.clone(),
+            presentation_header.clone(),
+            messages,
+            stolen_license.signature.signature.clone(),
+            &HolderKeypair::generate().unwrap(),
+        )
+        .and_then(|proof| {
+            issuer_public_key.verify_proof(
+                stolen_license.signature.header.clone(),
+                presentation_header,
+                Vec::new(),
+                proof,
+            )
+        })
+        .unwrap_err();
+
+    // Homer's wallet still presents the license just fine.
+    let decision = request_access(client, "relying-party", "person").await;
+    assert!(decision.granted, "unexpected decision: {decision:?}");
+}
 
-    // TODO: simulate the person visiting a website and proving something to the relying party
+#[tokio::test]
+async fn upstream_error_propagated() {
+    let simulation = Simulation::sample().unwrap().start().await.unwrap();
+
+    // The DMV doesn't issue library cards. The person relays the DMV's refusal to its caller.
+    let response = simulation
+        .client()
+        .post("http://person/credential")
+        .json(&ObtainCredentialRequest {
+            credential_type: CredentialType::LibraryCard,
+            issuer: "issuer-dmv".to_string(),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+    let error: Error = response.json().await.unwrap();
+    let Error::Upstream { host, .. } = &error else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert_eq!(host, "issuer-dmv");
+    assert!(
+        matches!(error.root_cause(), Error::NotPermitted { .. }),
+        "unexpected error: {error:?}"
+    );
+}
+
+#[tokio::test]
+async fn provision_initial_credentials() {
+    let wallet: WalletConfiguration =
+        parse_configuration(include_str!("../../wallet/config/provisioned.yaml")).unwrap();
+    let mut issuer_library: IssuerConfiguration =
+        parse_configuration(include_str!("../../issuer/config/library.yaml")).unwrap();
+    issuer_library.storage = StorageConfiguration::Memory;
+    issuer_library.key_file = None;
+    let simulation = Simulation::builder()
+        .actor("wallet", wallet, didemo_wallet::actor)
+        .actor("issuer-library", issuer_library, didemo_issuer::actor)
+        .start()
+        .await
+        .unwrap();
+
+    // The wallet requests its library card in the background once it starts.
+    for _ in 0..50 {
+        let credentials: Vec<Credential> = simulation
+            .client()
+            .get("http://wallet/credentials")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if let [credential] = credentials.as_slice() {
+            assert_eq!(credential.credential_type, CredentialType::LibraryCard);
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("wallet was not provisioned");
 }
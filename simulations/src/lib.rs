@@ -1,2 +1,17 @@
+//! Simulations of digital identity deployments.
+
+pub mod harness;
+pub mod scenario;
+
+#[cfg(test)]
+mod audit;
+#[cfg(test)]
+mod consent;
 #[cfg(test)]
 mod issuance;
+#[cfg(test)]
+mod population;
+#[cfg(test)]
+mod renewal;
+#[cfg(test)]
+mod rotation;
@@ -0,0 +1,38 @@
+//! Run simulation scenarios and report the outcome of each of their steps.
+
+use clap::Parser;
+use didemo_simulations::scenario::Scenario;
+use std::{path::PathBuf, process::ExitCode};
+use tracing_subscriber::EnvFilter;
+
+/// Run simulation scenarios.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Cli {
+    /// Paths to scenario files, in YAML or JSON.
+    #[arg(required = true)]
+    scenarios: Vec<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> Result<ExitCode, anyhow::Error> {
+    // Actors log to the same output as the report, so keep them quiet unless asked otherwise.
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()))
+        .init();
+
+    let cli = Cli::parse();
+
+    let mut passed = true;
+    for path in cli.scenarios {
+        let report = Scenario::load(&path)?.run().await?;
+        println!("{}:\n{report}", path.display());
+        passed &= report.passed();
+    }
+
+    Ok(if passed {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
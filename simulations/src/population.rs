@@ -0,0 +1,124 @@
+use crate::harness::{Simulation, parse_configuration};
+use didemo_common::{
+    credential::{CredentialType, DriversLicense},
+    messages::{
+        person::ObtainCredentialRequest,
+        population::{PopulationAccessRequest, PopulationStatistics},
+    },
+    time::now,
+};
+use didemo_population::PopulationConfiguration;
+use std::collections::BTreeMap;
+
+fn population_configuration(seed: u64) -> PopulationConfiguration {
+    let mut config: PopulationConfiguration =
+        parse_configuration(include_str!("../../population/config/sample.yaml")).unwrap();
+    config.seed = seed;
+    config
+}
+
+#[test]
+fn seeded_persons() {
+    let persons = |seed| serde_json::to_value(population_configuration(seed).persons()).unwrap();
+    assert_eq!(persons(1), persons(1));
+    assert_ne!(persons(1), persons(2));
+}
+
+#[tokio::test]
+async fn population_statistics() {
+    let simulation = Simulation::sample().unwrap().start().await.unwrap();
+    let client = simulation.client();
+    let config = population_configuration(1);
+
+    // Each person is reachable at their own path prefix.
+    let hostnames: Vec<String> = client
+        .get("http://population/persons")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(hostnames.len(), config.size);
+    let person: serde_json::Value = client
+        .get(format!("http://{}/config", hostnames[3]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(person["name"], config.persons()[3].name);
+    assert_eq!(person["wallet_hostname"], "population/wallets/3");
+
+    let statistics: PopulationStatistics = client
+        .post("http://population/credential")
+        .json(&ObtainCredentialRequest {
+            credential_type: CredentialType::DriversLicense,
+            issuer: "issuer-dmv".to_string(),
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        statistics,
+        PopulationStatistics {
+            attempts: config.size,
+            successes: config.size,
+            failures: BTreeMap::new(),
+        }
+    );
+
+    // The library doesn't issue driver's licenses.
+    let statistics: PopulationStatistics = client
+        .post("http://population/credential")
+        .json(&ObtainCredentialRequest {
+            credential_type: CredentialType::DriversLicense,
+            issuer: "issuer-library".to_string(),
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(statistics.successes, 0);
+    assert_eq!(
+        statistics.failures,
+        BTreeMap::from([("not_permitted".to_string(), config.size)])
+    );
+
+    // The relying party turns away everyone under 21.
+    let over_21 = config
+        .persons()
+        .iter()
+        .filter(|person| DriversLicense::age_over(person.birthdate, now().unwrap())[1])
+        .count();
+    assert!(0 < over_21 && over_21 < config.size);
+
+    let statistics: PopulationStatistics = client
+        .post("http://population/access")
+        .json(&PopulationAccessRequest {
+            relying_party: "relying-party".to_string(),
+        })
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        statistics,
+        PopulationStatistics {
+            attempts: config.size,
+            successes: over_21,
+            failures: BTreeMap::from([(
+                r#"{"AgeOver":{"threshold":21}}"#.to_string(),
+                config.size - over_21
+            )]),
+        }
+    );
+}
@@ -0,0 +1,191 @@
+use crate::harness::{Simulation, obtain_drivers_license, parse_configuration, request_access};
+use didemo_common::{
+    credential::{Address, Credential, CredentialType, DriversLicense},
+    error::Error,
+    messages::{issuer::LedgerEntry, person::ObtainRenewalRequest},
+};
+use didemo_relying_party::RelyingPartyConfiguration;
+use reqwest::{Client, StatusCode};
+
+fn library(region: &str) -> RelyingPartyConfiguration {
+    parse_configuration(&format!(
+        r#"
+label: "library-{region}"
+required_proofs:
+  - !ResidesIn
+    country: "US"
+    region: "{region}"
+trusted_issuers:
+  - name: "issuer/dmv-1"
+    hostname: "issuer-dmv"
+check_revocation: true
+check_validity: true
+"#
+    ))
+    .unwrap()
+}
+
+/// Instruct the person to renew their driver's license. On failure, returns the code of the error
+/// the person relays from the issuer.
+async fn renew_drivers_license(client: &Client, serial_number: u64) -> Result<(), String> {
+    let response = client
+        .post("http://person/renewal")
+        .json(&ObtainRenewalRequest {
+            credential_type: CredentialType::DriversLicense,
+            issuer: "issuer-dmv".to_string(),
+            serial_number,
+        })
+        .send()
+        .await
+        .unwrap();
+    if response.status() == StatusCode::CREATED {
+        return Ok(());
+    }
+
+    let error: Error = response.json().await.unwrap();
+    Err(error.root_cause().code().to_string())
+}
+
+#[tokio::test]
+async fn address_change() {
+    let simulation = Simulation::sample()
+        .unwrap()
+        .actor("library-oh", library("OH"), didemo_relying_party::actor)
+        .actor("library-il", library("IL"), didemo_relying_party::actor)
+        .start()
+        .await
+        .unwrap();
+    let client = simulation.client();
+
+    obtain_drivers_license(client, "person").await;
+    assert!(request_access(client, "library-oh", "person").await.granted);
+
+    // Moving doesn't change the license the person already holds.
+    let new_address = Address {
+        street: "57 Walnut Street".to_string(),
+        city: "Ogdenville".to_string(),
+        region: "IL".to_string(),
+        postal_code: "62701".to_string(),
+        country: "US".to_string(),
+    };
+    let response = client
+        .put("http://person/home-address")
+        .json(&new_address)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(request_access(client, "library-oh", "person").await.granted);
+    assert!(!request_access(client, "library-il", "person").await.granted);
+
+    renew_drivers_license(client, 1).await.unwrap();
+
+    // The wallet holds only the renewed license, which gives the new address.
+    let credentials: Vec<Credential> = client
+        .get("http://wallet/credentials")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let drivers_licenses: Vec<DriversLicense> = credentials
+        .iter()
+        .filter(|credential| credential.credential_type == CredentialType::DriversLicense)
+        .map(|credential| serde_json::from_str(&credential.encoded_credential).unwrap())
+        .collect();
+    assert_eq!(drivers_licenses.len(), 1);
+    assert_eq!(drivers_licenses[0].serial_number, 2);
+    assert_eq!(drivers_licenses[0].home_address, new_address);
+
+    // The issuer's ledger links the old license and the new one, and the old one is revoked.
+    let ledger: Vec<LedgerEntry> = client
+        .get("http://issuer-dmv/ledger")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(ledger.len(), 2);
+    assert_eq!(ledger[0].serial_number, 1);
+    assert_eq!(ledger[0].superseded_by, Some(2));
+    assert!(ledger[0].revoked_at.is_some());
+    assert_eq!(ledger[1].serial_number, 2);
+    assert_eq!(ledger[1].supersedes, Some(1));
+    assert_eq!(ledger[1].revoked_at, None);
+
+    assert!(!request_access(client, "library-oh", "person").await.granted);
+    assert!(request_access(client, "library-il", "person").await.granted);
+    assert!(
+        request_access(client, "relying-party", "person")
+            .await
+            .granted
+    );
+
+    // A license can only be renewed once, and only if it exists.
+    assert_eq!(
+        renew_drivers_license(client, 1).await,
+        Err("not_permitted".to_string())
+    );
+    assert_eq!(
+        renew_drivers_license(client, 3).await,
+        Err("not_found".to_string())
+    );
+
+    // The issuer refuses such renewals before asking the wallet for a holder key, so they don't
+    // leave the wallet holding keys for credentials that never come, crowding out renewals that
+    // the issuer would grant.
+    for _ in 0..100 {
+        assert_eq!(
+            renew_drivers_license(client, 1).await,
+            Err("not_permitted".to_string())
+        );
+    }
+    renew_drivers_license(client, 2).await.unwrap();
+}
+
+#[tokio::test]
+async fn concurrent_renewals() {
+    let simulation = Simulation::sample().unwrap().start().await.unwrap();
+    let client = simulation.client();
+
+    obtain_drivers_license(client, "person").await;
+
+    // Only one of two renewals of the same license in flight at once succeeds.
+    let results = tokio::join!(
+        renew_drivers_license(client, 1),
+        renew_drivers_license(client, 1)
+    );
+    let mut results = [results.0, results.1];
+    results.sort();
+    assert_eq!(results, [Ok(()), Err("not_permitted".to_string())]);
+
+    let ledger: Vec<LedgerEntry> = client
+        .get("http://issuer-dmv/ledger")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(ledger.len(), 2);
+    assert_eq!(ledger[0].superseded_by, Some(2));
+    assert!(ledger[0].revoked_at.is_some());
+    assert_eq!(ledger[1].supersedes, Some(1));
+
+    let credentials: Vec<Credential> = client
+        .get("http://wallet/credentials")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(credentials.len(), 1);
+    assert!(
+        request_access(client, "relying-party", "person")
+            .await
+            .granted
+    );
+}
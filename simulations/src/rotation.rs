@@ -0,0 +1,366 @@
+use crate::harness::{
+    Simulation, SimulationBuilder, obtain_drivers_license, parse_configuration, request_access,
+};
+use axum::Router;
+use didemo_common::{
+    bbs::BbsPublicKey,
+    credential::{Credential, CredentialType},
+    messages::{
+        issuer::{IssuerPublicKey, RetireKeyRequest, RotateKeyRequest},
+        jurisdiction::JurisdictionPublicKey,
+        wallet_maker::WalletMakerPublicKey,
+    },
+    storage::StorageConfiguration,
+};
+use didemo_issuer::IssuerConfiguration;
+use didemo_jurisdiction::JurisdictionConfiguration;
+use didemo_wallet::WalletConfiguration;
+use didemo_wallet_maker::WalletMakerConfiguration;
+use reqwest::{Client, ClientBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use std::{env, fs, path::Path, process};
+
+async fn retire_key(client: &Client, key_id: &str) -> StatusCode {
+    client
+        .post("http://issuer-dmv/retire-key")
+        .json(&RetireKeyRequest {
+            key_id: key_id.to_string(),
+        })
+        .send()
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn old_credentials_verify_until_key_retired() {
+    let simulation = Simulation::sample().unwrap().start().await.unwrap();
+    let client = simulation.client();
+
+    obtain_drivers_license(client, "person").await;
+    let original_key: IssuerPublicKey = client
+        .get("http://issuer-dmv/public-key")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(
+        request_access(client, "relying-party", "person")
+            .await
+            .granted
+    );
+
+    // A retirement too far in the future to represent is refused, without rotating.
+    let response = client
+        .post("http://issuer-dmv/rotate-key")
+        .json(&RotateKeyRequest {
+            retire_after: Some(u64::MAX),
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let rotated_key: IssuerPublicKey = client
+        .post("http://issuer-dmv/rotate-key")
+        .json(&RotateKeyRequest::default())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_ne!(rotated_key.key_id, original_key.key_id);
+
+    // The issuer publishes both keys, and the credential signed with the original key still
+    // verifies, even though the status list is now signed with the rotated key.
+    let keys: Vec<IssuerPublicKey> = client
+        .get("http://issuer-dmv/public-keys")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(keys, [original_key.clone(), rotated_key.clone()]);
+    assert!(
+        request_access(client, "relying-party", "person")
+            .await
+            .granted
+    );
+
+    // The issuer won't retire the key it signs with, but once it retires the original key, the
+    // credential signed with it no longer verifies.
+    assert_eq!(
+        retire_key(client, &rotated_key.key_id).await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        retire_key(client, &original_key.key_id).await,
+        StatusCode::OK
+    );
+    assert!(
+        !request_access(client, "relying-party", "person")
+            .await
+            .granted
+    );
+
+    // A credential issued since the rotation names the rotated key, and the wallet prefers it.
+    obtain_drivers_license(client, "person").await;
+    let credentials: Vec<Credential> = client
+        .get("http://wallet/credentials")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let key_ids: Vec<_> = credentials
+        .iter()
+        .filter(|credential| credential.credential_type == CredentialType::DriversLicense)
+        .map(|credential| credential.signature.key_id.as_str())
+        .collect();
+    assert_eq!(
+        key_ids,
+        [original_key.key_id.as_str(), rotated_key.key_id.as_str()]
+    );
+    assert!(
+        request_access(client, "relying-party", "person")
+            .await
+            .granted
+    );
+}
+
+#[tokio::test]
+async fn key_file_keeps_key_across_restarts() {
+    let key_file = env::temp_dir().join(format!("didemo-issuer-key-{}", process::id()));
+    let public_key_file = key_file.with_extension("pub");
+    let mut issuer_dmv: IssuerConfiguration =
+        parse_configuration(include_str!("../../issuer/config/dmv.yaml")).unwrap();
+    issuer_dmv.storage = Default::default();
+    issuer_dmv.key_file = Some(key_file.clone());
+
+    // The first issuer generates the key, and the second, as after a restart, reads it back.
+    let mut published_keys = Vec::new();
+    for _ in 0..2 {
+        let simulation = Simulation::builder()
+            .actor("issuer-dmv", issuer_dmv.clone(), didemo_issuer::actor)
+            .start()
+            .await
+            .unwrap();
+        let published_key: IssuerPublicKey = simulation
+            .client()
+            .get("http://issuer-dmv/public-key")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        published_keys.push(published_key);
+    }
+    assert_eq!(published_keys[0].key_id, published_keys[1].key_id);
+    assert_eq!(published_keys[0].public_key, published_keys[1].public_key);
+    assert_eq!(
+        BbsPublicKey::read_from_file(&public_key_file).unwrap(),
+        published_keys[0].public_key
+    );
+
+    fs::remove_file(key_file).unwrap();
+    fs::remove_file(public_key_file).unwrap();
+}
+
+/// Start just the actor and fetch the public key it publishes.
+async fn fetch_public_key<C: Send + 'static, K: DeserializeOwned>(
+    hostname: &str,
+    config: C,
+    actor: fn(C, ClientBuilder) -> Result<(String, Router), anyhow::Error>,
+) -> K {
+    let simulation = Simulation::builder()
+        .actor(hostname, config, actor)
+        .start()
+        .await
+        .unwrap();
+    simulation
+        .client()
+        .get(format!("http://{hostname}/public-key"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn jurisdiction_key_file_keeps_key_across_restarts() {
+    let dir = env::temp_dir().join(format!("didemo-jurisdiction-key-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut jurisdiction: JurisdictionConfiguration =
+        parse_configuration(include_str!("../../jurisdiction/config/sample.yaml")).unwrap();
+    jurisdiction.key_file = Some(dir.join("jurisdiction.key"));
+
+    // Wallets and verifiers pin the jurisdiction's key, so the second jurisdiction, as after a
+    // restart, must read back the key the first generated.
+    let first: JurisdictionPublicKey = fetch_public_key(
+        "jurisdiction",
+        jurisdiction.clone(),
+        didemo_jurisdiction::actor,
+    )
+    .await;
+    let second: JurisdictionPublicKey =
+        fetch_public_key("jurisdiction", jurisdiction, didemo_jurisdiction::actor).await;
+    assert_eq!(first, second);
+    assert_eq!(
+        BbsPublicKey::read_from_file(&dir.join("jurisdiction.key.pub")).unwrap(),
+        first.public_key
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn wallet_maker_key_file_keeps_key_across_restarts() {
+    let dir = env::temp_dir().join(format!("didemo-wallet-maker-key-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut wallet_maker: WalletMakerConfiguration =
+        parse_configuration(include_str!("../../wallet_maker/config/sample.yaml")).unwrap();
+    wallet_maker.key_file = Some(dir.join("wallet-maker.key"));
+
+    // Issuers pin the wallet maker's key, so the second wallet maker, as after a restart, must read
+    // back the key the first generated.
+    let first: WalletMakerPublicKey = fetch_public_key(
+        "wallet-maker",
+        wallet_maker.clone(),
+        didemo_wallet_maker::actor,
+    )
+    .await;
+    let second: WalletMakerPublicKey =
+        fetch_public_key("wallet-maker", wallet_maker, didemo_wallet_maker::actor).await;
+    assert_eq!(first, second);
+    assert_eq!(
+        BbsPublicKey::read_from_file(&dir.join("wallet-maker.key.pub")).unwrap(),
+        first.public_key
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// The sample simulation, minus the library, auditor and population, with the wallet, the DMV, the
+/// wallet maker and the jurisdiction keeping their state and keys in files in the directory, so
+/// that they survive a restart.
+fn persistent_simulation(dir: &Path) -> SimulationBuilder {
+    let mut wallet: WalletConfiguration =
+        parse_configuration(include_str!("../../wallet/config/sample.yaml")).unwrap();
+    wallet.storage = StorageConfiguration::JsonFile {
+        path: dir.join("credentials.json"),
+    };
+    wallet.secret_storage = StorageConfiguration::JsonFile {
+        path: dir.join("holder-secrets.json"),
+    };
+
+    let mut issuer_dmv: IssuerConfiguration =
+        parse_configuration(include_str!("../../issuer/config/dmv.yaml")).unwrap();
+    issuer_dmv.storage = StorageConfiguration::JsonFile {
+        path: dir.join("issuer-dmv.json"),
+    };
+    issuer_dmv.key_file = Some(dir.join("issuer-dmv.key"));
+
+    let mut wallet_maker: WalletMakerConfiguration =
+        parse_configuration(include_str!("../../wallet_maker/config/sample.yaml")).unwrap();
+    wallet_maker.key_file = Some(dir.join("wallet-maker.key"));
+
+    let mut jurisdiction: JurisdictionConfiguration =
+        parse_configuration(include_str!("../../jurisdiction/config/sample.yaml")).unwrap();
+    jurisdiction.key_file = Some(dir.join("jurisdiction.key"));
+
+    Simulation::builder()
+        .actor(
+            "person",
+            parse_configuration(include_str!("../../person/config/sample.yaml")).unwrap(),
+            didemo_person::actor,
+        )
+        .actor("wallet", wallet, didemo_wallet::actor)
+        .actor("wallet-maker", wallet_maker, didemo_wallet_maker::actor)
+        .actor("issuer-dmv", issuer_dmv, didemo_issuer::actor)
+        .actor("jurisdiction", jurisdiction, didemo_jurisdiction::actor)
+        .actor(
+            "relying-party",
+            parse_configuration(include_str!("../../relying_party/config/sample.yaml")).unwrap(),
+            didemo_relying_party::actor,
+        )
+}
+
+#[tokio::test]
+async fn rotated_keys_survive_restarts() {
+    let dir = env::temp_dir().join(format!("didemo-rotated-keys-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let keys = {
+        let simulation = persistent_simulation(&dir).start().await.unwrap();
+        let client = simulation.client();
+
+        obtain_drivers_license(client, "person").await;
+        let response = client
+            .post("http://issuer-dmv/rotate-key")
+            .json(&RotateKeyRequest {
+                retire_after: Some(3600),
+            })
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let keys: Vec<IssuerPublicKey> = client
+            .get("http://issuer-dmv/public-keys")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(keys.len(), 2);
+        keys
+    };
+
+    // After a restart, the issuer still publishes the key it rotated away from, with the same
+    // validity window, so the credential signed with it still verifies. It signs with the rotated
+    // key, which it read back from the key file.
+    let simulation = persistent_simulation(&dir).start().await.unwrap();
+    let client = simulation.client();
+    let restarted_keys: Vec<IssuerPublicKey> = client
+        .get("http://issuer-dmv/public-keys")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(restarted_keys, keys);
+    let signing_key: IssuerPublicKey = client
+        .get("http://issuer-dmv/public-key")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(signing_key, keys[1]);
+
+    let credentials: Vec<Credential> = client
+        .get("http://wallet/credentials")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(credentials.len(), 1);
+    assert_eq!(credentials[0].signature.key_id, keys[0].key_id);
+    let decision = request_access(client, "relying-party", "person").await;
+    assert!(decision.granted, "access denied: {decision:?}");
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,607 @@
+//! Declarative simulation scenarios.
+//!
+//! A scenario names the actors to run, along with their configurations, and a sequence of steps to
+//! perform against them, each with an expected outcome. Scenarios are written in YAML or JSON; see
+//! the `scenarios` directory for examples. Run scenarios with
+//! `cargo run --package didemo_simulations -- <scenario file>...`.
+
+use crate::harness::Simulation;
+use anyhow::{Context, anyhow};
+use didemo_auditor::AuditorConfiguration;
+use didemo_common::{
+    credential::CredentialType,
+    error::{Error, send},
+    messages::{
+        issuer::{IssuerPublicKey, RevokeCredentialRequest},
+        person::{ObtainCredentialRequest, Proof, ProofRequest, ProofType},
+        relying_party::{AccessDecision, AccessRequest},
+    },
+    time::now,
+    trust::{TrustRegistry, TrustedIssuer},
+};
+use didemo_issuer::IssuerConfiguration;
+use didemo_jurisdiction::JurisdictionConfiguration;
+use didemo_person::PersonConfiguration;
+use didemo_population::PopulationConfiguration;
+use didemo_relying_party::RelyingPartyConfiguration;
+use didemo_wallet::WalletConfiguration;
+use didemo_wallet_maker::WalletMakerConfiguration;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::Path,
+};
+
+/// A simulation scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    /// What the scenario demonstrates.
+    #[serde(default)]
+    pub description: String,
+
+    /// Actors to run, keyed by the hostname at which other actors reach them.
+    pub actors: BTreeMap<String, ActorConfiguration>,
+
+    /// Steps to perform, in order.
+    pub steps: Vec<Step>,
+}
+
+/// The kind of an actor in a scenario, and its configuration. Actors keep their state in memory
+/// unless configured otherwise.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorConfiguration {
+    Person(PersonConfiguration),
+    Wallet(WalletConfiguration),
+    WalletMaker(WalletMakerConfiguration),
+    Issuer(IssuerConfiguration),
+    Jurisdiction(JurisdictionConfiguration),
+    RelyingParty(RelyingPartyConfiguration),
+    Auditor(AuditorConfiguration),
+    Population(PopulationConfiguration),
+}
+
+/// A step in a scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    /// Identifies the step in reports. Defaults to a description of the action.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// What to do.
+    pub action: Action,
+
+    /// The expected outcome of the action.
+    #[serde(default)]
+    pub expect: Expectation,
+}
+
+/// An action performed in a step of a scenario.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Instruct a person to obtain a credential from an issuer.
+    ObtainCredential {
+        person: String,
+        issuer: String,
+        credential_type: CredentialType,
+    },
+    /// Ask a person for a proof and verify it against the public key and status list published by
+    /// the issuer.
+    RequestProof {
+        person: String,
+        issuer: String,
+        proof_type: ProofType,
+        #[serde(default)]
+        revocation_check: bool,
+        #[serde(default)]
+        validity_check: bool,
+    },
+    /// Ask a relying party to grant a person access. Succeeds if access is granted.
+    RequestAccess {
+        relying_party: String,
+        person: String,
+    },
+    /// Instruct an issuer to revoke a credential.
+    Revoke { issuer: String, serial_number: u64 },
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ObtainCredential {
+                person,
+                issuer,
+                credential_type,
+            } => write!(f, "{person} obtains {credential_type:?} from {issuer}"),
+            Self::RequestProof {
+                person,
+                issuer,
+                proof_type,
+                ..
+            } => write!(
+                f,
+                "{person} proves {proof_type:?} with credential from {issuer}"
+            ),
+            Self::RequestAccess {
+                relying_party,
+                person,
+            } => write!(f, "{relying_party} grants {person} access"),
+            Self::Revoke {
+                issuer,
+                serial_number,
+            } => write!(f, "{issuer} revokes credential {serial_number}"),
+        }
+    }
+}
+
+/// The expected outcome of a step.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Expectation {
+    /// Whether the step should succeed.
+    #[serde(default = "default_success")]
+    pub success: bool,
+
+    /// The code of the error a failing step should fail with (e.g. `not_permitted`). If the error
+    /// was relayed from another actor, this is the code of the error that actor reported.
+    #[serde(default)]
+    pub error: Option<String>,
+
+    /// Text that the failure message of a failing step should contain.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+fn default_success() -> bool {
+    true
+}
+
+impl Default for Expectation {
+    fn default() -> Self {
+        Self {
+            success: default_success(),
+            error: None,
+            message: None,
+        }
+    }
+}
+
+/// Why a step failed.
+struct StepFailure {
+    /// Code of the error at the root of the failure, if it was a typed error.
+    code: Option<String>,
+    message: String,
+}
+
+impl From<anyhow::Error> for StepFailure {
+    fn from(error: anyhow::Error) -> Self {
+        let code = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<Error>())
+            .map(|error| error.root_cause().code().to_string());
+
+        Self {
+            code,
+            message: format!("{error:#}"),
+        }
+    }
+}
+
+impl From<Error> for StepFailure {
+    fn from(error: Error) -> Self {
+        anyhow::Error::from(error).into()
+    }
+}
+
+impl Scenario {
+    /// Load a scenario from a file. Files with a `.json` extension are parsed as JSON and any
+    /// others as YAML.
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents =
+            fs::read_to_string(path).context(format!("failed to read {}", path.display()))?;
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            serde_json::from_str(&contents).context(format!("failed to parse {}", path.display()))
+        } else {
+            serde_yaml::from_str(&contents).context(format!("failed to parse {}", path.display()))
+        }
+    }
+
+    /// Run the scenario's actors and perform each of its steps, even if earlier steps fail.
+    pub async fn run(&self) -> Result<ScenarioReport, anyhow::Error> {
+        let simulation = self
+            .actors
+            .iter()
+            .fold(
+                Simulation::builder(),
+                |builder, (hostname, config)| match config.clone() {
+                    ActorConfiguration::Person(config) => {
+                        builder.actor(hostname, config, didemo_person::actor)
+                    }
+                    ActorConfiguration::Wallet(config) => {
+                        builder.actor(hostname, config, didemo_wallet::actor)
+                    }
+                    ActorConfiguration::WalletMaker(config) => {
+                        builder.actor(hostname, config, didemo_wallet_maker::actor)
+                    }
+                    ActorConfiguration::Issuer(config) => {
+                        builder.actor(hostname, config, didemo_issuer::actor)
+                    }
+                    ActorConfiguration::Jurisdiction(config) => {
+                        builder.actor(hostname, config, didemo_jurisdiction::actor)
+                    }
+                    ActorConfiguration::RelyingParty(config) => {
+                        builder.actor(hostname, config, didemo_relying_party::actor)
+                    }
+                    ActorConfiguration::Auditor(config) => {
+                        builder.actor(hostname, config, didemo_auditor::actor)
+                    }
+                    ActorConfiguration::Population(config) => {
+                        builder.actor(hostname, config, didemo_population::actor)
+                    }
+                },
+            )
+            .start()
+            .await?;
+
+        let mut steps = Vec::new();
+        for step in &self.steps {
+            let result = perform(simulation.client(), &step.action).await;
+            let (passed, detail) = check(&step.expect, result);
+            steps.push(StepReport {
+                name: step.name.clone().unwrap_or_else(|| step.action.to_string()),
+                passed,
+                detail,
+            });
+        }
+
+        Ok(ScenarioReport {
+            description: self.description.clone(),
+            steps,
+        })
+    }
+}
+
+/// Perform the action.
+async fn perform(client: &Client, action: &Action) -> Result<(), StepFailure> {
+    match action {
+        Action::ObtainCredential {
+            person,
+            issuer,
+            credential_type,
+        } => {
+            send(
+                person,
+                client
+                    .post(format!("http://{person}/credential"))
+                    .json(&ObtainCredentialRequest {
+                        credential_type: *credential_type,
+                        issuer: issuer.clone(),
+                    }),
+            )
+            .await?;
+        }
+        Action::RequestProof {
+            person,
+            issuer,
+            proof_type,
+            revocation_check,
+            validity_check,
+        } => {
+            request_proof(
+                client,
+                person,
+                issuer,
+                proof_type,
+                *revocation_check,
+                *validity_check,
+            )
+            .await?;
+        }
+        Action::RequestAccess {
+            relying_party,
+            person,
+        } => {
+            let decision: AccessDecision = send(
+                relying_party,
+                client
+                    .post(format!("http://{relying_party}/access"))
+                    .json(&AccessRequest {
+                        person_hostname: person.clone(),
+                    }),
+            )
+            .await?
+            .json()
+            .await
+            .context("failed to deserialize access decision")?;
+
+            if !decision.granted {
+                let failures: Vec<_> = decision
+                    .proof_outcomes
+                    .iter()
+                    .filter_map(|outcome| {
+                        outcome
+                            .failure
+                            .as_ref()
+                            .map(|failure| format!("{:?}: {failure}", outcome.proof_type))
+                    })
+                    .collect();
+                return Err(StepFailure {
+                    code: None,
+                    message: format!("access denied: {}", failures.join("; ")),
+                });
+            }
+        }
+        Action::Revoke {
+            issuer,
+            serial_number,
+        } => {
+            send(
+                issuer,
+                client
+                    .post(format!("http://{issuer}/revoke"))
+                    .json(&RevokeCredentialRequest {
+                        serial_number: *serial_number,
+                    }),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ask a person for a proof and verify it, trusting only the issuer.
+async fn request_proof(
+    client: &Client,
+    person: &str,
+    issuer: &str,
+    proof_type: &ProofType,
+    revocation_check: bool,
+    validity_check: bool,
+) -> Result<(), anyhow::Error> {
+    let issuer_public_key: IssuerPublicKey =
+        send(issuer, client.get(format!("http://{issuer}/public-key")))
+            .await?
+            .json()
+            .await
+            .context("failed to deserialize issuer public key")?;
+    let trust_registry = TrustRegistry::new(
+        Vec::from([TrustedIssuer {
+            name: issuer_public_key.issuer,
+            hostname: issuer.to_string(),
+        }]),
+        client.clone(),
+    );
+
+    let proof_request = ProofRequest {
+        proof_type: proof_type.clone(),
+        verifier: "scenario".to_string(),
+        nonce: rand::random::<[u8; 16]>().to_vec(),
+        revocation_check,
+        validity_check,
+    };
+    let proof: Proof = send(
+        person,
+        client
+            .get(format!("http://{person}/proof"))
+            .json(&proof_request),
+    )
+    .await?
+    .json()
+    .await
+    .context("failed to deserialize proof")?;
+
+    trust_registry
+        .resolve(&proof.header, &proof.key_id)
+        .await?
+        .verify_proof(
+            proof.header.clone(),
+            proof_request.presentation_header(),
+            proof.disclosed_messages.clone(),
+            proof.proof.clone(),
+        )?;
+
+    let checked_attributes = proof_request
+        .check_disclosed_messages(proof.credential_type()?, &proof.disclosed_messages)?;
+
+    let now = now()?;
+    if let Some(valid_until) = checked_attributes.valid_until
+        && valid_until <= now
+    {
+        return Err(anyhow!("credential has expired"));
+    }
+
+    if let Some(status_list_index) = checked_attributes.status_list_index {
+        let status_list = trust_registry.status_list(&proof.header).await?;
+        if status_list.is_revoked(status_list_index)? {
+            return Err(anyhow!("credential has been revoked"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the result of a step against the expectation. Returns whether the step passed and any
+/// detail worth reporting.
+fn check(expect: &Expectation, result: Result<(), StepFailure>) -> (bool, Option<String>) {
+    let failure = match (expect.success, result) {
+        (true, Ok(())) => return (true, None),
+        (true, Err(failure)) => return (false, Some(failure.message)),
+        (false, Ok(())) => return (false, Some("expected failure but succeeded".to_string())),
+        (false, Err(failure)) => failure,
+    };
+
+    if let Some(error) = &expect.error
+        && failure.code.as_ref() != Some(error)
+    {
+        return (
+            false,
+            Some(format!(
+                "expected error {error} but got {}: {}",
+                failure.code.as_deref().unwrap_or("untyped error"),
+                failure.message
+            )),
+        );
+    }
+
+    if let Some(message) = &expect.message
+        && !failure.message.contains(message)
+    {
+        return (
+            false,
+            Some(format!(
+                "expected failure mentioning \"{message}\" but got: {}",
+                failure.message
+            )),
+        );
+    }
+
+    (true, Some(failure.message))
+}
+
+/// The outcome of running a scenario.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioReport {
+    /// What the scenario demonstrates.
+    pub description: String,
+
+    /// The outcome of each step, in order.
+    pub steps: Vec<StepReport>,
+}
+
+impl ScenarioReport {
+    /// Whether every step passed.
+    pub fn passed(&self) -> bool {
+        self.steps.iter().all(|step| step.passed)
+    }
+}
+
+impl Display for ScenarioReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.description.is_empty() {
+            writeln!(f, "{}", self.description)?;
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            write!(
+                f,
+                "{} {}. {}",
+                if step.passed { "PASS" } else { "FAIL" },
+                index + 1,
+                step.name
+            )?;
+            if let Some(detail) = &step.detail {
+                write!(f, " ({detail})")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of a step in a scenario.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepReport {
+    /// Identifies the step.
+    pub name: String,
+
+    /// Whether the step had the expected outcome.
+    pub passed: bool,
+
+    /// Why the step failed, or, if it was expected to fail, how it failed.
+    pub detail: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scenario;
+    use std::{fs, path::Path};
+
+    #[tokio::test]
+    async fn scenarios() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+        let mut paths: Vec<_> = fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty());
+
+        for path in paths {
+            let report = Scenario::load(&path).unwrap().run().await.unwrap();
+            assert!(report.passed(), "{}:\n{report}", path.display());
+        }
+    }
+
+    #[tokio::test]
+    async fn unexpected_outcomes_fail() {
+        let scenario: Scenario = serde_yaml::from_str(
+            r#"
+actors:
+  person: !person
+    name: "Homer Simpson"
+    home_address:
+      street: "742 Evergreen Terrace"
+      city: "Springfield"
+      region: "OH"
+      postal_code: "45501"
+      country: "US"
+    organ_donor: true
+    birthdate: 326937600
+    wallet_hostname: wallet
+  wallet: !wallet
+    vendor: "didemo-wallet-maker"
+    trusted_issuers:
+      - name: "issuer/dmv-1"
+        hostname: "issuer-dmv"
+  issuer-dmv: !issuer
+    label: "dmv-1"
+    credential_types:
+      - "DriversLicense"
+steps:
+  - action: !obtain_credential
+      person: person
+      issuer: issuer-dmv
+      credential_type: LibraryCard
+  - action: !obtain_credential
+      person: person
+      issuer: issuer-dmv
+      credential_type: LibraryCard
+    expect:
+      success: false
+      error: invalid_signature
+  - action: !obtain_credential
+      person: person
+      issuer: issuer-dmv
+      credential_type: LibraryCard
+    expect:
+      success: false
+      error: not_permitted
+  - action: !revoke
+      issuer: issuer-dmv
+      serial_number: 1
+    expect:
+      success: false
+      message: "serial number 1"
+  - action: !obtain_credential
+      person: person
+      issuer: issuer-dmv
+      credential_type: DriversLicense
+    expect:
+      success: false
+"#,
+        )
+        .unwrap();
+
+        let report = scenario.run().await.unwrap();
+        let passed: Vec<_> = report.steps.iter().map(|step| step.passed).collect();
+        assert_eq!(passed, [false, false, true, true, false], "{report}");
+        assert!(!report.passed());
+    }
+}
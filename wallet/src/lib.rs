@@ -1 +1,731 @@
+//! Simulation of a wallet in a digital identity deployment.
 
+use anyhow::{Context, anyhow};
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, post, put},
+};
+use didemo_common::{
+    attestation::WalletAttestation,
+    bbs::{BbsPublicKey, HolderKeypair},
+    config::{CommonConfiguration, Configuration},
+    credential::{Credential, CredentialType},
+    error::{Error, send},
+    messages::{
+        issuer::IssueCredentialRequest,
+        person::{Proof, ProofRequest},
+        wallet::{
+            HolderBinding, HolderBindingRequest, ReplaceCredentialRequest, StoreCredentialRequest,
+        },
+        wallet_maker::AttestationRequest,
+    },
+    router::AppError,
+    storage::{StorageConfiguration, Store},
+    time::now,
+    trust::{TrustRegistry, TrustedIssuer, TrustedJurisdiction},
+};
+use reqwest::{Client, ClientBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Configuration for a wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletConfiguration {
+    #[serde(flatten)]
+    pub common: CommonConfiguration,
+
+    /// The wallet vendor's name.
+    pub vendor: String,
+
+    /// Issuers whose credentials this wallet will accept.
+    pub trusted_issuers: Vec<TrustedIssuer>,
+
+    /// Jurisdictions trusted to accredit issuers. If any are configured, the wallet only accepts
+    /// credentials from issuers accredited by one of them to issue that type of credential.
+    #[serde(default)]
+    pub trusted_jurisdictions: Vec<TrustedJurisdiction>,
+
+    /// Where the wallet persists credentials.
+    #[serde(default)]
+    pub storage: StorageConfiguration,
+
+    /// Where the wallet persists the secrets that bind credentials to it. These are kept apart from
+    /// the credentials, so that a copy of the credentials is useless without them.
+    #[serde(default)]
+    pub secret_storage: StorageConfiguration,
+
+    /// The hostname at which issuers can reach this wallet. Required if any initial credentials
+    /// are to be requested from issuers, or if the wallet is to obtain an attestation.
+    #[serde(default)]
+    pub hostname: Option<String>,
+
+    /// The hostname at which the wallet's maker can be reached. If set, the wallet obtains an
+    /// attestation from its maker the first time an issuer asks for one.
+    #[serde(default)]
+    pub wallet_maker: Option<String>,
+
+    /// Credentials the wallet is provisioned with when it first starts, before any credentials
+    /// have been stored.
+    #[serde(default)]
+    pub initial_credentials: BTreeMap<CredentialType, InitialCredential>,
+}
+
+/// A credential the wallet is provisioned with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum InitialCredential {
+    /// A credential that was already issued, bound to a holder key the wallet is given along with
+    /// it.
+    Issued {
+        /// The credential.
+        credential: Credential,
+
+        /// The secret key of the holder key the credential is bound to.
+        holder_secret_key: Vec<u8>,
+    },
+    /// A credential the wallet requests from an issuer.
+    Request {
+        /// The issuer to obtain the credential from, as a DNS name that this actor can resolve.
+        issuer: String,
+
+        /// A JSON blob, whose format is dictated by the credential type, describing the credential
+        /// being requested.
+        requested_credential: String,
+    },
+}
+
+impl Configuration for WalletConfiguration {
+    fn common_configuration(&self) -> &CommonConfiguration {
+        &self.common
+    }
+}
+
+/// A secret binding a credential to this wallet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HolderSecret {
+    /// The signature of the bound credential.
+    signature: Vec<u8>,
+    /// The secret key of the holder key the issuer bound the credential to. The issuer only ever
+    /// saw the public key.
+    secret: Vec<u8>,
+}
+
+/// A holder key handed to an issuer, which the wallet remembers until the credential bound to it
+/// arrives.
+#[derive(Debug)]
+struct PendingHolderBinding {
+    /// The issuer the key was handed to, named as in the headers of its signatures.
+    issuer: String,
+    keypair: HolderKeypair,
+    /// When the wallet forgets the key, in seconds since the UNIX epoch.
+    expires_at: u64,
+}
+
+/// Maximum number of holder keys handed out to issuers that the wallet remembers at once. Pending
+/// keys are never evicted to make room for others, since an issuer may be about to deliver a
+/// credential bound to them, so the wallet refuses to hand out more until some expire.
+const MAX_PENDING_HOLDER_BINDINGS: usize = 64;
+
+/// How long the wallet remembers a holder key handed out to an issuer, in seconds.
+const PENDING_HOLDER_BINDING_LIFETIME: u64 = 300;
+
+#[derive(Debug)]
+struct Wallet {
+    config: WalletConfiguration,
+    http_client: Client,
+    trust_registry: TrustRegistry,
+    attestation: Option<WalletAttestation>,
+    credential_store: Box<dyn Store<Vec<Credential>>>,
+    credentials: Vec<Credential>,
+    holder_secret_store: Box<dyn Store<Vec<HolderSecret>>>,
+    holder_secrets: Vec<HolderSecret>,
+    /// Holder keys handed out to issuers, by binding ID.
+    pending_holder_bindings: HashMap<String, PendingHolderBinding>,
+}
+
+impl Wallet {
+    /// The holder key the credential is bound to, if the wallet has it.
+    fn holder_keypair(&self, credential: &Credential) -> Option<HolderKeypair> {
+        self.holder_secrets
+            .iter()
+            .find(|holder_secret| holder_secret.signature == credential.signature.signature)
+            .and_then(|holder_secret| HolderKeypair::from_secret_key(&holder_secret.secret).ok())
+    }
+
+    /// The holder key handed out with the binding ID, which the credential must be bound to. Fails
+    /// if the key wasn't handed to the credential's issuer.
+    fn pending_holder_keypair(
+        &self,
+        binding_id: &str,
+        credential: &Credential,
+    ) -> Result<HolderKeypair, Error> {
+        let pending = self
+            .pending_holder_bindings
+            .get(binding_id)
+            .filter(|pending| pending.expires_at > now().unwrap_or(u64::MAX))
+            .ok_or_else(|| Error::NotFound {
+                message: format!("no pending holder binding {binding_id}"),
+            })?;
+        if pending.issuer.as_bytes() != credential.signature.header {
+            return Err(Error::NotPermitted {
+                message: format!(
+                    "holder binding {binding_id} is for {}, not {}",
+                    pending.issuer,
+                    String::from_utf8_lossy(&credential.signature.header)
+                ),
+            });
+        }
+
+        Ok(pending.keypair.clone())
+    }
+}
+
+/// Construct a wallet from its configuration. Returns the actor's name and the routes it serves.
+pub fn actor(
+    config: WalletConfiguration,
+    client_builder: ClientBuilder,
+) -> Result<(String, Router), anyhow::Error> {
+    Ok((
+        "wallet".to_string(),
+        routes(config, client_builder.build()?)?,
+    ))
+}
+
+/// Construct the routes a wallet serves, given the HTTP client with which it makes requests.
+pub fn routes(config: WalletConfiguration, http_client: Client) -> Result<Router, anyhow::Error> {
+    let credential_store: Box<dyn Store<Vec<Credential>>> = config.storage.open();
+    let stored_credentials = credential_store
+        .load()
+        .context("failed to load credentials")?;
+    // If credentials were stored, then the wallet was provisioned on an earlier start.
+    let first_start = stored_credentials.is_none();
+    let stored_credentials = stored_credentials.unwrap_or_default();
+    tracing::info!(count = stored_credentials.len(), "loaded credentials");
+
+    let holder_secret_store: Box<dyn Store<Vec<HolderSecret>>> = config.secret_storage.open();
+    let holder_secrets = holder_secret_store
+        .load()
+        .context("failed to load holder secrets")?
+        .unwrap_or_default();
+
+    let wallet = Arc::new(Mutex::new(Wallet {
+        trust_registry: TrustRegistry::new(config.trusted_issuers.clone(), http_client.clone())
+            .with_jurisdictions(config.trusted_jurisdictions.clone()),
+        config: config.clone(),
+        http_client: http_client.clone(),
+        attestation: None,
+        credential_store,
+        credentials: stored_credentials,
+        holder_secret_store,
+        holder_secrets,
+        pending_holder_bindings: HashMap::new(),
+    }));
+
+    if first_start && !config.initial_credentials.is_empty() {
+        // Issuers put credentials into the wallet over HTTP, so provisioning has to happen
+        // once the wallet is serving requests.
+        tokio::spawn(provision(Arc::clone(&wallet), http_client, config));
+    }
+
+    Ok(Router::new()
+        .route("/config", get(serve_config))
+        .route("/credentials", get(credentials))
+        .route("/credentials", put(store_credential))
+        .route("/replace-credential", post(replace_credential))
+        .route("/proof", get(prove))
+        .route("/attestation", get(attestation))
+        .route("/holder-bindings", post(holder_binding))
+        .with_state(wallet))
+}
+
+/// Print the configuration.
+async fn serve_config(State(wallet): State<Arc<Mutex<Wallet>>>) -> Json<WalletConfiguration> {
+    Json(wallet.lock().unwrap().config.clone())
+}
+
+/// Print all the credentials stored in the wallet.
+async fn credentials(State(wallet): State<Arc<Mutex<Wallet>>>) -> Json<Vec<Credential>> {
+    Json(wallet.lock().unwrap().credentials.clone())
+}
+
+/// Present the wallet maker's attestation that it made this wallet, obtaining it from the wallet
+/// maker if the wallet doesn't have it yet.
+async fn attestation(
+    State(wallet): State<Arc<Mutex<Wallet>>>,
+) -> Result<Json<WalletAttestation>, AppError> {
+    let (config, http_client) = {
+        let wallet = wallet.lock().unwrap();
+        if let Some(attestation) = &wallet.attestation {
+            return Ok(Json(attestation.clone()));
+        }
+        (wallet.config.clone(), wallet.http_client.clone())
+    };
+
+    let (Some(wallet_maker), Some(hostname)) = (config.wallet_maker, config.hostname) else {
+        return Err(Error::NotFound {
+            message: "wallet has no attestation".to_string(),
+        }
+        .into());
+    };
+
+    let attestation: WalletAttestation = send(
+        &wallet_maker,
+        http_client
+            .post(format!("http://{wallet_maker}/attestations"))
+            .json(&AttestationRequest {
+                wallet_hostname: hostname,
+            }),
+    )
+    .await?
+    .json()
+    .await
+    .context("failed to deserialize attestation")?;
+
+    if attestation.vendor != config.vendor {
+        return Err(anyhow!(
+            "{wallet_maker} attested for vendor {} rather than {}",
+            attestation.vendor,
+            config.vendor
+        )
+        .into());
+    }
+
+    tracing::info!(vendor = attestation.vendor, "obtained wallet attestation");
+    wallet.lock().unwrap().attestation = Some(attestation.clone());
+
+    Ok(Json(attestation))
+}
+
+/// Hand a trusted issuer a fresh holder key, to which to bind the credential it is about to issue
+/// into this wallet, keeping the secret key. Anyone may ask for one in an issuer's name, but what
+/// they get binds no credential the wallet already holds, and is only good for a credential from
+/// that issuer.
+async fn holder_binding(
+    State(wallet): State<Arc<Mutex<Wallet>>>,
+    Json(request): Json<HolderBindingRequest>,
+) -> Result<Json<HolderBinding>, AppError> {
+    let mut wallet = wallet.lock().unwrap();
+    if !wallet
+        .config
+        .trusted_issuers
+        .iter()
+        .any(|issuer| issuer.name == request.issuer)
+    {
+        return Err(Error::UntrustedIssuer {
+            message: format!("wallet does not trust issuer {}", request.issuer),
+        }
+        .into());
+    }
+
+    let now = now()?;
+    wallet
+        .pending_holder_bindings
+        .retain(|_, pending| pending.expires_at > now);
+    if wallet.pending_holder_bindings.len() >= MAX_PENDING_HOLDER_BINDINGS {
+        return Err(Error::NotPermitted {
+            message: "too many holder bindings pending, try again later".to_string(),
+        }
+        .into());
+    }
+
+    let keypair = HolderKeypair::generate()?;
+    let binding = HolderBinding {
+        binding_id: format!("{:032x}", rand::random::<u128>()),
+        public_key: keypair.public_key(),
+        proof_of_possession: keypair.prove_possession(request.issuer.as_bytes())?,
+    };
+    wallet.pending_holder_bindings.insert(
+        binding.binding_id.clone(),
+        PendingHolderBinding {
+            issuer: request.issuer,
+            keypair,
+            expires_at: now.saturating_add(PENDING_HOLDER_BINDING_LIFETIME),
+        },
+    );
+
+    Ok(Json(binding))
+}
+
+/// Store the credential in the wallet.
+async fn store_credential(
+    State(wallet): State<Arc<Mutex<Wallet>>>,
+    Json(request): Json<StoreCredentialRequest>,
+) -> Result<StatusCode, AppError> {
+    add_pending_credential(&wallet, &request.binding_id, request.credential, None).await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// Replace a credential in the wallet with its renewal.
+async fn replace_credential(
+    State(wallet): State<Arc<Mutex<Wallet>>>,
+    Json(request): Json<ReplaceCredentialRequest>,
+) -> Result<StatusCode, AppError> {
+    add_pending_credential(
+        &wallet,
+        &request.binding_id,
+        request.credential,
+        Some(request.serial_number),
+    )
+    .await?;
+
+    Ok(StatusCode::CREATED)
+}
+
+/// Add a credential bound to the holder key handed out with the binding ID, which the wallet then
+/// forgets.
+async fn add_pending_credential(
+    wallet: &Arc<Mutex<Wallet>>,
+    binding_id: &str,
+    credential: Credential,
+    replaces: Option<u64>,
+) -> Result<(), anyhow::Error> {
+    let holder_keypair = wallet
+        .lock()
+        .unwrap()
+        .pending_holder_keypair(binding_id, &credential)?;
+    add_credential(wallet, credential, holder_keypair, replaces).await?;
+    wallet
+        .lock()
+        .unwrap()
+        .pending_holder_bindings
+        .remove(binding_id);
+
+    Ok(())
+}
+
+/// Verify the credential, which must be bound to the holder key, and add it to the wallet, in place
+/// of the credential of the same type from the same issuer with the serial number `replaces`, if
+/// any.
+async fn add_credential(
+    wallet: &Arc<Mutex<Wallet>>,
+    request: Credential,
+    holder_keypair: HolderKeypair,
+    replaces: Option<u64>,
+) -> Result<(), anyhow::Error> {
+    // TODO: policy checks? For uniqueness on certain keys?
+
+    let trust_registry = wallet.lock().unwrap().trust_registry.clone();
+    let issuer_public_key = trust_registry
+        .resolve(&request.signature.header, &request.signature.key_id)
+        .await?;
+
+    // Verify the BBS signature (not any proof on any message!) so that we don't accept credentials
+    // forged in the name of a trusted issuer. The signature only verifies with the secret key of
+    // the holder key it is bound to, so a credential that isn't bound to the key is rejected too.
+    let messages = request.bbs_messages().map_err(|error| Error::BadRequest {
+        message: format!("{error:#}"),
+    })?;
+    issuer_public_key.verify_bound(
+        request.signature.header.clone(),
+        messages,
+        request.signature.signature.clone(),
+        &holder_keypair,
+    )?;
+    trust_registry
+        .check_accreditation(&request.signature.header, request.credential_type)
+        .await?;
+
+    let mut wallet = wallet.lock().unwrap();
+    let replaced = match replaces {
+        Some(serial_number) => Some(
+            wallet
+                .credentials
+                .iter()
+                .position(|credential| {
+                    credential.credential_type == request.credential_type
+                        && credential.signature.header == request.signature.header
+                        && credential.integer_attribute("serial_number").ok() == Some(serial_number)
+                })
+                .ok_or_else(|| Error::NotFound {
+                    message: format!(
+                        "no {:?} with serial number {serial_number} from this issuer",
+                        request.credential_type
+                    ),
+                })?,
+        ),
+        None => None,
+    };
+
+    // Store the secret first: a credential that outlives its secret is useless.
+    wallet.holder_secrets.push(HolderSecret {
+        signature: request.signature.signature.clone(),
+        secret: holder_keypair.secret_key(),
+    });
+    if let Err(error) = wallet.holder_secret_store.store(&wallet.holder_secrets) {
+        wallet.holder_secrets.pop();
+        return Err(error);
+    }
+    let mut credentials = wallet.credentials.clone();
+    let replaced = replaced.map(|index| credentials.remove(index));
+    credentials.push(request);
+    // Don't hold on to a credential that would be lost on restart.
+    wallet.credential_store.store(&credentials)?;
+    wallet.credentials = credentials;
+
+    // The replaced credential's secret is of no further use.
+    if let Some(replaced) = replaced {
+        let mut holder_secrets = wallet.holder_secrets.clone();
+        holder_secrets.retain(|secret| secret.signature != replaced.signature.signature);
+        match wallet.holder_secret_store.store(&holder_secrets) {
+            Ok(()) => wallet.holder_secrets = holder_secrets,
+            Err(error) => tracing::warn!(?error, "failed to discard replaced credential's secret"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Provision the wallet with its initial credentials.
+async fn provision(wallet: Arc<Mutex<Wallet>>, http_client: Client, config: WalletConfiguration) {
+    for (credential_type, initial_credential) in config.initial_credentials {
+        let result = match initial_credential {
+            InitialCredential::Issued {
+                credential,
+                holder_secret_key,
+            } => {
+                if credential.credential_type == credential_type {
+                    match HolderKeypair::from_secret_key(&holder_secret_key) {
+                        Ok(holder_keypair) => {
+                            add_credential(&wallet, credential, holder_keypair, None).await
+                        }
+                        Err(error) => Err(error),
+                    }
+                } else {
+                    Err(anyhow!(
+                        "initial credential is a {:?}",
+                        credential.credential_type
+                    ))
+                }
+            }
+            InitialCredential::Request {
+                issuer,
+                requested_credential,
+            } => {
+                request_credential(
+                    &http_client,
+                    &issuer,
+                    IssueCredentialRequest {
+                        credential_type,
+                        requested_credential,
+                        wallet_hostname: config.hostname.clone().unwrap_or_default(),
+                    },
+                )
+                .await
+            }
+        };
+
+        match result {
+            Ok(()) => tracing::info!(?credential_type, "provisioned initial credential"),
+            Err(error) => tracing::error!(
+                ?credential_type,
+                "failed to provision initial credential: {error:?}"
+            ),
+        }
+    }
+}
+
+/// Ask an issuer to issue a credential into this wallet, retrying for a while in case the issuer
+/// is still starting up.
+async fn request_credential(
+    http_client: &Client,
+    issuer: &str,
+    issue_request: IssueCredentialRequest,
+) -> Result<(), anyhow::Error> {
+    if issue_request.wallet_hostname.is_empty() {
+        return Err(anyhow!(
+            "wallet hostname must be configured to request credentials"
+        ));
+    }
+
+    const ATTEMPTS: u32 = 10;
+    let mut attempt = 1;
+    loop {
+        let error = match send(
+            issuer,
+            http_client
+                .put(format!("http://{issuer}/issue"))
+                .json(&issue_request),
+        )
+        .await
+        {
+            Ok(_) => return Ok(()),
+            Err(error) => error,
+        };
+
+        if attempt == ATTEMPTS {
+            return Err(error.into());
+        }
+        tracing::debug!(attempt, "failed to request initial credential: {error:?}");
+        attempt += 1;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Prove to a verifier that a message is signed.
+#[axum::debug_handler]
+async fn prove(
+    State(wallet): State<Arc<Mutex<Wallet>>>,
+    Json(proof_request): Json<ProofRequest>,
+) -> Result<Json<Proof>, AppError> {
+    tracing::info!(proof_type = ?proof_request.proof_type, "proving credential attribute");
+
+    // Determine which messages to disclose from each acceptable type of credential, and the values
+    // that any of those messages must have.
+    let selection = proof_request.selection()?;
+    let mut messages_by_type = Vec::new();
+    let mut selection_error = None;
+    for credential_type in &selection.credential_types {
+        match selection.messages(*credential_type) {
+            Ok(messages) => messages_by_type.push((*credential_type, messages)),
+            Err(error) => selection_error = Some(error),
+        }
+    }
+    if messages_by_type.is_empty() {
+        return Err(selection_error
+            .unwrap_or_else(|| Error::BadRequest {
+                message: "no acceptable credential types".to_string(),
+            })
+            .into());
+    }
+
+    // Expired credentials are useless to verifiers, so there's no point proving anything from them.
+    let now = now()?;
+
+    // Don't hold the lock while resolving the issuer's key.
+    let (credential, selected_messages, holder_keypair, trust_registry) = {
+        let wallet = wallet.lock().unwrap();
+        // Prefer credentials of the types the verifier listed first and, among those, the most
+        // recently issued credential, which is the least likely to be signed with a key its issuer
+        // has since retired.
+        let (credential, selected_messages, holder_keypair) = messages_by_type
+            .iter()
+            .flat_map(|(credential_type, selected_messages)| {
+                wallet
+                    .credentials
+                    .iter()
+                    .rev()
+                    .filter(move |credential| credential.credential_type == *credential_type)
+                    .map(move |credential| (credential, selected_messages))
+            })
+            .filter(|(credential, _)| {
+                selection.accepts(credential.credential_type, &credential.signature.header)
+                    && credential.valid_at(now).unwrap_or(false)
+            })
+            .filter_map(|(credential, selected_messages)| {
+                Some((
+                    credential,
+                    selected_messages,
+                    wallet.holder_keypair(credential)?,
+                ))
+            })
+            .find_map(|(credential, selected_messages, holder_keypair)| {
+                let messages = credential.bbs_messages().ok()?;
+                selected_messages
+                    .iter()
+                    .all(|(index, value)| {
+                        value
+                            .as_ref()
+                            .is_none_or(|value| messages.get(*index) == Some(value))
+                    })
+                    .then(|| {
+                        (
+                            credential.clone(),
+                            selected_messages.clone(),
+                            holder_keypair,
+                        )
+                    })
+            })
+            .ok_or_else(|| Error::NoMatchingCredential {
+                message: format!(
+                    "found no credential in wallet that can satisfy {:?}",
+                    proof_request.proof_type
+                ),
+            })?;
+        (
+            credential,
+            selected_messages,
+            holder_keypair,
+            wallet.trust_registry.clone(),
+        )
+    };
+
+    // Always disclose the credential's type, so that the verifier can tell what it is.
+    let mut disclosed_indices = Vec::from([CredentialType::MESSAGE_INDEX]);
+    disclosed_indices.extend(selected_messages.into_iter().map(|(index, _)| index));
+    if proof_request.revocation_check {
+        disclosed_indices.push(credential.credential_type.status_message_index());
+    }
+    if proof_request.validity_check {
+        disclosed_indices.push(credential.credential_type.valid_until_message_index());
+    }
+
+    let issuer_public_key = trust_registry
+        .resolve(&credential.signature.header, &credential.signature.key_id)
+        .await?;
+
+    Ok(Json(prove_credential(
+        &credential,
+        &holder_keypair,
+        &issuer_public_key,
+        &proof_request.presentation_header(),
+        &disclosed_indices,
+    )?))
+}
+
+/// Construct a proof from the credential bound to the presentation header, disclosing the messages
+/// at the given indices. If the credential is bound to this wallet, the proof requires the secret
+/// that binds it, which is never disclosed.
+fn prove_credential(
+    credential: &Credential,
+    holder_keypair: &HolderKeypair,
+    issuer_public_key: &BbsPublicKey,
+    presentation_header: &[u8],
+    disclosed_indices: &[usize],
+) -> Result<Proof, anyhow::Error> {
+    // The proof algorithm needs all the messages, even those that aren't disclosed.
+    let messages: Vec<_> = credential
+        .bbs_messages()?
+        .into_iter()
+        .enumerate()
+        .map(|(index, message)| (disclosed_indices.contains(&index), message))
+        .collect();
+
+    let disclosed_messages: Vec<_> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, (disclosed, _))| *disclosed)
+        .map(|(index, (_, message))| (index, message.clone()))
+        .collect();
+
+    let proof = issuer_public_key.prove(
+        credential.signature.header.clone(),
+        presentation_header.to_vec(),
+        messages,
+        credential.signature.signature.clone(),
+        holder_keypair,
+    )?;
+
+    if let Err(error) = issuer_public_key.verify_proof(
+        credential.signature.header.clone(),
+        presentation_header.to_vec(),
+        disclosed_messages.clone(),
+        proof.clone(),
+    ) {
+        tracing::info!(
+            credential_type = ?credential.credential_type,
+            "failed to verify proof: {error:?}"
+        );
+    }
+
+    Ok(Proof {
+        header: credential.signature.header.clone(),
+        key_id: credential.signature.key_id.clone(),
+        proof,
+        disclosed_messages,
+    })
+}
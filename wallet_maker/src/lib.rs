@@ -0,0 +1,110 @@
+//! Simulation of a wallet maker in a digital identity deployment.
+
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, post},
+};
+use didemo_common::{
+    attestation::WalletAttestation,
+    bbs::BbsKeypair,
+    config::{CommonConfiguration, Configuration},
+    messages::wallet_maker::{AttestationRequest, WalletMakerPublicKey},
+    router::AppError,
+};
+use reqwest::ClientBuilder;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+
+/// Configuration for a wallet maker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletMakerConfiguration {
+    #[serde(flatten)]
+    pub common: CommonConfiguration,
+
+    /// The vendor name under which the wallet maker attests to wallets.
+    pub vendor: String,
+
+    /// File holding the private part of the wallet maker's signing key, as written by
+    /// [`BbsKeypair::write_to_key_files`]. If the file doesn't exist, the wallet maker generates a
+    /// key and writes it there, along with the public part in a file with `.pub` appended to the
+    /// name. If not configured, the wallet maker generates a new key every time it starts, and
+    /// issuers, which pin the key they first fetched, reject its attestations until they restart
+    /// too.
+    #[serde(default)]
+    pub key_file: Option<PathBuf>,
+}
+
+impl Configuration for WalletMakerConfiguration {
+    fn common_configuration(&self) -> &CommonConfiguration {
+        &self.common
+    }
+}
+
+#[derive(Debug)]
+struct WalletMaker {
+    config: WalletMakerConfiguration,
+    bbs_keypair: BbsKeypair,
+}
+
+/// Construct a wallet maker from its configuration. Returns the actor's name and the routes it
+/// serves.
+pub fn actor(
+    config: WalletMakerConfiguration,
+    _client_builder: ClientBuilder,
+) -> Result<(String, Router), anyhow::Error> {
+    let actor_name = format!("wallet-maker/{}", config.vendor);
+
+    let bbs_keypair = match &config.key_file {
+        Some(key_file) => BbsKeypair::read_or_generate(key_file)?,
+        None => BbsKeypair::generate()?,
+    };
+    let wallet_maker = WalletMaker {
+        config,
+        bbs_keypair,
+    };
+
+    let routes = Router::new()
+        .route("/config", get(serve_config))
+        .route("/public-key", get(serve_public_key))
+        .route("/attestations", post(attest))
+        .with_state(Arc::new(wallet_maker));
+
+    Ok((actor_name, routes))
+}
+
+/// Print the configuration.
+async fn serve_config(
+    State(wallet_maker): State<Arc<WalletMaker>>,
+) -> Json<WalletMakerConfiguration> {
+    Json(wallet_maker.config.clone())
+}
+
+/// Publish the wallet maker's public key, so issuers can check its attestations.
+async fn serve_public_key(
+    State(wallet_maker): State<Arc<WalletMaker>>,
+) -> Json<WalletMakerPublicKey> {
+    Json(WalletMakerPublicKey {
+        vendor: wallet_maker.config.vendor.clone(),
+        public_key: wallet_maker.bbs_keypair.public_key(),
+    })
+}
+
+/// Attest that this wallet maker made a wallet.
+async fn attest(
+    State(wallet_maker): State<Arc<WalletMaker>>,
+    Json(request): Json<AttestationRequest>,
+) -> Result<Json<WalletAttestation>, AppError> {
+    // This is where a wallet maker would check that the wallet really is one of its own, for
+    // instance using platform attestation. We do not simulate that and attest to any wallet.
+    tracing::info!(
+        wallet_hostname = request.wallet_hostname,
+        "attesting to wallet"
+    );
+
+    Ok(Json(WalletAttestation::sign(
+        &wallet_maker.config.vendor,
+        &request.wallet_hostname,
+        &wallet_maker.bbs_keypair,
+    )?))
+}